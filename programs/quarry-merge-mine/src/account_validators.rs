@@ -113,9 +113,9 @@ impl<'info> Validate<'info> for ClaimRewards<'info> {
     }
 }
 
-/// --------------------------------
-/// Context Structs
-/// --------------------------------
+// --------------------------------
+// Context Structs
+// --------------------------------
 
 impl<'info> Validate<'info> for QuarryStakePrimary<'info> {
     fn validate(&self) -> Result<()> {
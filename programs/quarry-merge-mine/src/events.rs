@@ -24,6 +24,17 @@ pub struct InitMergeMinerEvent {
     pub owner: Pubkey,
 }
 
+/// Emitted when a [MergeMiner] is closed.
+#[event]
+pub struct CloseMergeMinerEvent {
+    /// The [MergePool].
+    pub pool: Pubkey,
+    /// The [MergeMiner].
+    pub mm: Pubkey,
+    /// Owner of the [MergeMiner].
+    pub owner: Pubkey,
+}
+
 /// Emitted when a new [quarry_mine::Miner] is created.
 #[event]
 pub struct InitMinerEvent {
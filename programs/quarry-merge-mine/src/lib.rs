@@ -1,6 +1,7 @@
 //! Holds tokens to allow one depositor to mine multiple quarries at the same time.
 #![deny(rustdoc::all)]
 #![allow(rustdoc::missing_doc_code_examples)]
+#![allow(clippy::result_large_err)]
 #![allow(deprecated)]
 
 #[macro_use]
@@ -35,7 +36,7 @@ solana_security_txt::security_txt! {
 
 declare_id!("QMMD16kjauP5knBwxNUJRZ1Z5o3deBuFrqVjBVmmqto");
 
-#[deny(clippy::integer_arithmetic, clippy::float_arithmetic)]
+#[deny(clippy::arithmetic_side_effects, clippy::float_arithmetic)]
 #[program]
 /// Quarry merge mining program.
 pub mod quarry_merge_mine {
@@ -130,6 +131,17 @@ pub mod quarry_merge_mine {
         processor::withdraw::withdraw_tokens(ctx)
     }
 
+    /// Closes a [MergeMiner] with no primary or replica balance.
+    ///
+    /// The [quarry_mine::Miner]s owned by the [MergeMiner] must be supplied as remaining accounts
+    /// and may not hold any balance or unclaimed rewards.
+    #[access_control(ctx.accounts.validate())]
+    pub fn close_merge_miner<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseMergeMiner<'info>>,
+    ) -> Result<()> {
+        processor::close_merge_miner::handler(ctx)
+    }
+
     /// Rescues stuck tokens in miners owned by a [MergeMiner].
     #[access_control(ctx.accounts.validate())]
     pub fn rescue_tokens(ctx: Context<RescueTokens>) -> Result<()> {
@@ -422,4 +434,6 @@ pub enum ErrorCode {
     ReplicaDecimalsMismatch,
     #[msg("The replica mint must have zero supply.")]
     ReplicaNonZeroSupply,
+    #[msg("The merge miner still has a primary or replica balance, or a miner with unclaimed rewards.")]
+    MergeMinerNotEmpty,
 }
@@ -1,5 +1,5 @@
 //! CPI calls made on behalf of a [crate::MergeMiner].
-#![deny(clippy::integer_arithmetic, clippy::float_arithmetic)]
+#![deny(clippy::arithmetic_side_effects, clippy::float_arithmetic)]
 
 use crate::events::WithdrawTokensEvent;
 use crate::{
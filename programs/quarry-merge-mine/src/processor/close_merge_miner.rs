@@ -0,0 +1,67 @@
+//! Implementation of the [crate::quarry_merge_mine::close_merge_miner] instruction.
+
+use crate::{events::*, *};
+
+/// Handler for the [crate::quarry_merge_mine::close_merge_miner] instruction.
+///
+/// The [quarry_mine::Miner]s of the [MergeMiner] must be supplied as remaining accounts,
+/// and each must have no balance or unclaimed rewards.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CloseMergeMiner<'info>>) -> Result<()> {
+    let mm_key = ctx.accounts.mm.key();
+    for info in ctx.remaining_accounts.iter() {
+        let miner: Account<quarry_mine::Miner> = Account::try_from(info)?;
+        assert_keys_eq!(miner.authority, mm_key, Unauthorized);
+        invariant!(
+            miner.balance == 0
+                && miner.rewards_earned == 0
+                && miner.unbonding_balance == 0
+                && miner.penalties_earned == 0
+                && miner
+                    .reward_streams
+                    .iter()
+                    .all(|stream| stream.rewards_earned == 0),
+            MergeMinerNotEmpty
+        );
+    }
+
+    let pool = &mut ctx.accounts.pool;
+    pool.mm_count = unwrap_int!(pool.mm_count.checked_sub(1));
+
+    emit!(CloseMergeMinerEvent {
+        pool: pool.key(),
+        mm: ctx.accounts.mm.key(),
+        owner: ctx.accounts.owner.key(),
+    });
+
+    Ok(())
+}
+
+/// Accounts for the [crate::quarry_merge_mine::close_merge_miner] instruction.
+#[derive(Accounts)]
+pub struct CloseMergeMiner<'info> {
+    /// The [MergeMiner::owner].
+    pub owner: Signer<'info>,
+    /// The [MergePool].
+    #[account(mut)]
+    pub pool: Account<'info, MergePool>,
+    /// The [MergeMiner] to close.
+    #[account(mut, close = rent_recipient)]
+    pub mm: Account<'info, MergeMiner>,
+    /// Receives the rent of the [MergeMiner].
+    /// CHECK: Arbitrary account.
+    #[account(mut)]
+    pub rent_recipient: UncheckedAccount<'info>,
+}
+
+impl<'info> Validate<'info> for CloseMergeMiner<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.owner, self.mm.owner, Unauthorized);
+        assert_keys_eq!(self.pool, self.mm.pool);
+        assert_keys_neq!(self.rent_recipient, self.mm);
+        invariant!(
+            self.mm.primary_balance == 0 && self.mm.replica_balance == 0,
+            MergeMinerNotEmpty
+        );
+        Ok(())
+    }
+}
@@ -1,10 +1,12 @@
 //! Processes incoming instructions.
-#![deny(clippy::integer_arithmetic, clippy::float_arithmetic)]
+#![deny(clippy::arithmetic_side_effects, clippy::float_arithmetic)]
 
 pub(crate) mod claim;
+pub mod close_merge_miner;
 pub(crate) mod deposit;
 pub(crate) mod init;
 pub mod rescue_tokens;
 pub(crate) mod withdraw;
 
+pub use close_merge_miner::*;
 pub use rescue_tokens::*;
@@ -5,9 +5,10 @@ use vipers::prelude::*;
 
 use crate::addresses;
 use crate::{
-    AcceptAuthority, ExtractFees, MutableRewarderWithAuthority, MutableRewarderWithPauseAuthority,
-    ReadOnlyRewarderWithAuthority, SetAnnualRewards, SetFamine, SetPauseAuthority, SetRewardsShare,
-    TransferAuthority, UpdateQuarryRewards, UserStake,
+    AcceptAuthority, CommitRewardsShares, ExtractFees, MutableRewarderWithAuthority,
+    MutableRewarderWithPauseAuthority, ReadOnlyRewarderWithAuthority, SetAnnualRewards,
+    SetEmissionSchedule, SetFamine, SetPauseAuthority, SetRewardsShare, TransferAuthority,
+    UpdateQuarryRewards, UserStake,
 };
 
 // --------------------------------
@@ -67,6 +68,22 @@ impl<'info> Validate<'info> for SetAnnualRewards<'info> {
     }
 }
 
+impl<'info> Validate<'info> for SetEmissionSchedule<'info> {
+    fn validate(&self) -> Result<()> {
+        self.auth.rewarder.assert_not_paused()?;
+        self.auth.validate()?;
+        Ok(())
+    }
+}
+
+impl<'info> Validate<'info> for CommitRewardsShares<'info> {
+    fn validate(&self) -> Result<()> {
+        self.auth.rewarder.assert_not_paused()?;
+        self.auth.validate()?;
+        Ok(())
+    }
+}
+
 // --------------------------------
 // Quarry functions
 // --------------------------------
@@ -97,15 +114,21 @@ impl<'info> Validate<'info> for UpdateQuarryRewards<'info> {
     }
 }
 
-/// --------------------------------
-/// Miner functions
-/// --------------------------------
+// --------------------------------
+// Miner functions
+// --------------------------------
 
 impl<'info> Validate<'info> for UserStake<'info> {
     /// Validates the UserStake.
     fn validate(&self) -> Result<()> {
         self.rewarder.assert_not_paused()?;
+        self.validate_accounts()
+    }
+}
 
+impl<'info> UserStake<'info> {
+    /// Validates the accounts of the UserStake, whether or not the [Rewarder] is paused.
+    pub(crate) fn validate_accounts(&self) -> Result<()> {
         // authority
         invariant!(self.authority.is_signer, Unauthorized);
         assert_keys_eq!(self.authority, self.miner.authority);
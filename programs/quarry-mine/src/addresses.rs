@@ -20,5 +20,5 @@ pub mod fee_setter {
 /// Account authorized to take fees.
 pub static FEE_TO: Pubkey = fee_to::ID;
 
-/// Account authorized to set fees of a rewarder. Currently unused.
+/// Account authorized to set the claim fee of a rewarder.
 pub static FEE_SETTER: Pubkey = fee_setter::ID;
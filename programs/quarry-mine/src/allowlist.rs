@@ -0,0 +1,85 @@
+//! Merkle allowlists of [crate::Miner] authorities.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+
+/// Computes the leaf of an allowlist for a [crate::Miner] authority.
+pub fn compute_allowlist_leaf(authority: &Pubkey) -> [u8; 32] {
+    keccak::hashv(&[authority.as_ref()]).0
+}
+
+/// Returns true if `proof` shows that `authority` is in the allowlist with the given root.
+///
+/// Each pair of nodes is hashed in sorted order, so the proof does not need to specify
+/// on which side each node lies.
+pub fn verify_allowlist_proof(proof: &[[u8; 32]], root: [u8; 32], authority: &Pubkey) -> bool {
+    let computed_root = proof
+        .iter()
+        .fold(compute_allowlist_leaf(authority), |node, sibling| {
+            if node <= *sibling {
+                keccak::hashv(&[&node, sibling]).0
+            } else {
+                keccak::hashv(&[sibling, &node]).0
+            }
+        });
+    computed_root == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        if a <= b {
+            keccak::hashv(&[&a, &b]).0
+        } else {
+            keccak::hashv(&[&b, &a]).0
+        }
+    }
+
+    #[test]
+    fn test_verify_allowlist_proof() {
+        let authorities: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = authorities.iter().map(compute_allowlist_leaf).collect();
+        let left = hash_pair(leaves[0], leaves[1]);
+        let right = hash_pair(leaves[2], leaves[3]);
+        let root = hash_pair(left, right);
+
+        assert!(verify_allowlist_proof(
+            &[leaves[1], right],
+            root,
+            &authorities[0]
+        ));
+        assert!(verify_allowlist_proof(
+            &[leaves[0], right],
+            root,
+            &authorities[1]
+        ));
+        assert!(verify_allowlist_proof(
+            &[leaves[3], left],
+            root,
+            &authorities[2]
+        ));
+        assert!(verify_allowlist_proof(
+            &[leaves[2], left],
+            root,
+            &authorities[3]
+        ));
+
+        // wrong authority or proof
+        assert!(!verify_allowlist_proof(
+            &[leaves[1], right],
+            root,
+            &authorities[2]
+        ));
+        assert!(!verify_allowlist_proof(&[leaves[1]], root, &authorities[0]));
+        assert!(!verify_allowlist_proof(
+            &[leaves[1], right],
+            root,
+            &Pubkey::new_unique()
+        ));
+
+        // a single authority is its own root
+        assert!(verify_allowlist_proof(&[], leaves[0], &authorities[0]));
+    }
+}
@@ -0,0 +1,110 @@
+//! Historical [Miner::balance] checkpoints.
+
+use crate::*;
+
+impl MinerCheckpoints {
+    /// Records the balance of the [Miner] as of `current_ts`.
+    ///
+    /// Changes within the same timestamp overwrite the latest [BalanceCheckpoint];
+    /// otherwise, the oldest [BalanceCheckpoint] is overwritten once the buffer is full.
+    pub fn record(&mut self, current_ts: i64, balance: u64) -> Result<()> {
+        if let Some(latest) = self.latest_index() {
+            let latest = &mut self.checkpoints[latest];
+            invariant!(current_ts >= latest.timestamp, InvalidTimestamp);
+            if current_ts == latest.timestamp {
+                latest.balance = balance;
+                return Ok(());
+            }
+        }
+        let index = self.ring_index(self.num_checkpoints);
+        self.checkpoints[index] = BalanceCheckpoint {
+            timestamp: current_ts,
+            balance,
+        };
+        self.num_checkpoints = unwrap_int!(self.num_checkpoints.checked_add(1));
+        Ok(())
+    }
+
+    /// Returns the balance of the [Miner] as of `timestamp`, or [None] if
+    /// `timestamp` is older than every retained [BalanceCheckpoint].
+    pub fn balance_at(&self, timestamp: i64) -> Option<u64> {
+        let num_retained = self.num_checkpoints.min(MAX_BALANCE_CHECKPOINTS as u64);
+        (1..=num_retained)
+            .map(|age| &self.checkpoints[self.ring_index(self.num_checkpoints - age)])
+            .find(|checkpoint| checkpoint.timestamp <= timestamp)
+            .map(|checkpoint| checkpoint.balance)
+    }
+
+    fn latest_index(&self) -> Option<usize> {
+        self.num_checkpoints
+            .checked_sub(1)
+            .map(|num| self.ring_index(num))
+    }
+
+    fn ring_index(&self, num: u64) -> usize {
+        (num % MAX_BALANCE_CHECKPOINTS as u64) as usize
+    }
+}
+
+/// Records the [Miner::balance] in the [Miner::checkpoints], if the [Miner] has any.
+///
+/// The [MinerCheckpoints] must then be supplied as a writable remaining account.
+pub(crate) fn record_balance_checkpoint<'info>(
+    miner: &Miner,
+    remaining_accounts: &[AccountInfo<'info>],
+    current_ts: i64,
+) -> Result<()> {
+    if miner.checkpoints == Pubkey::default() {
+        return Ok(());
+    }
+    let info = unwrap_opt!(
+        remaining_accounts
+            .iter()
+            .find(|info| *info.key == miner.checkpoints),
+        MissingMinerCheckpoints
+    );
+    invariant!(info.is_writable, MissingMinerCheckpoints);
+    let mut checkpoints: Account<MinerCheckpoints> = Account::try_from(info)?;
+    checkpoints.record(current_ts, miner.balance)?;
+    checkpoints.exit(&crate::ID)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_balance_checkpoints() {
+        let mut checkpoints = MinerCheckpoints::default();
+        assert_eq!(checkpoints.balance_at(0), None);
+
+        checkpoints.record(100, 10).unwrap();
+        checkpoints.record(200, 20).unwrap();
+        checkpoints.record(200, 25).unwrap();
+        assert_eq!(checkpoints.num_checkpoints, 2);
+        assert!(checkpoints.record(150, 0).is_err());
+
+        assert_eq!(checkpoints.balance_at(99), None);
+        assert_eq!(checkpoints.balance_at(100), Some(10));
+        assert_eq!(checkpoints.balance_at(199), Some(10));
+        assert_eq!(checkpoints.balance_at(200), Some(25));
+        assert_eq!(checkpoints.balance_at(i64::MAX), Some(25));
+
+        // wrap around the ring, dropping the oldest entries
+        for i in 0..MAX_BALANCE_CHECKPOINTS as i64 {
+            checkpoints.record(1_000 + i, i as u64).unwrap();
+        }
+        assert_eq!(
+            checkpoints.num_checkpoints,
+            MAX_BALANCE_CHECKPOINTS as u64 + 2
+        );
+        assert_eq!(checkpoints.balance_at(200), None);
+        assert_eq!(checkpoints.balance_at(999), None);
+        assert_eq!(checkpoints.balance_at(1_000), Some(0));
+        assert_eq!(checkpoints.balance_at(1_010), Some(10));
+        assert_eq!(
+            checkpoints.balance_at(i64::MAX),
+            Some(MAX_BALANCE_CHECKPOINTS as u64 - 1)
+        );
+    }
+}
@@ -0,0 +1,352 @@
+//! Emission schedule math.
+
+use anchor_lang::prelude::*;
+use vipers::prelude::*;
+
+use crate::{EmissionPoint, EmissionSchedule, MAX_BPS, MAX_EMISSION_POINTS};
+
+impl EmissionSchedule {
+    /// Creates an [EmissionSchedule] from its points, validating them.
+    pub fn try_from_points(points: &[EmissionPoint]) -> Result<Self> {
+        invariant!(points.len() <= MAX_EMISSION_POINTS, InvalidEmissionSchedule);
+        let mut schedule = EmissionSchedule {
+            num_points: points.len() as u8,
+            ..Default::default()
+        };
+        for (i, point) in points.iter().enumerate() {
+            invariant!(
+                point.ts >= 0 && point.ts <= u32::MAX.into(),
+                InvalidEmissionSchedule
+            );
+            invariant!(point.multiplier_bps <= MAX_BPS, InvalidEmissionSchedule);
+            if i >= 1 {
+                invariant!(points[i - 1].ts <= point.ts, InvalidEmissionSchedule);
+            }
+            if i >= 2 {
+                // at most two points may share a timestamp
+                invariant!(points[i - 2].ts < point.ts, InvalidEmissionSchedule);
+            }
+            schedule.points[i] = *point;
+        }
+        Ok(schedule)
+    }
+
+    /// Returns true if the schedule has no points, i.e. the rate is constant.
+    pub fn is_empty(&self) -> bool {
+        self.num_points == 0
+    }
+
+    /// The points of the schedule in use.
+    pub fn active_points(&self) -> &[EmissionPoint] {
+        let num_points = (self.num_points as usize).min(MAX_EMISSION_POINTS);
+        &self.points[..num_points]
+    }
+
+    /// Computes the multiplier at `ts`, in BPS, rounded down.
+    pub fn multiplier_at_unsafe(&self, ts: i64) -> Option<u64> {
+        let points = self.active_points();
+        let first = points.first()?;
+        if ts < first.ts {
+            return Some(first.multiplier_bps);
+        }
+        for pair in points.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            if ts < end.ts {
+                let elapsed = i128::from(ts.checked_sub(start.ts)?);
+                let length = i128::from(end.ts.checked_sub(start.ts)?);
+                let delta =
+                    i128::from(end.multiplier_bps).checked_sub(start.multiplier_bps.into())?;
+                let multiplier = i128::from(start.multiplier_bps)
+                    .checked_mul(length)?
+                    .checked_add(delta.checked_mul(elapsed)?)?
+                    .checked_div(length)?;
+                return multiplier.try_into().ok();
+            }
+        }
+        Some(points.last()?.multiplier_bps)
+    }
+
+    /// Computes twice the integral of the multiplier from the first point up to `ts`, in BPS-seconds.
+    ///
+    /// Only the integral within the segment containing `ts` is rounded down. The integral
+    /// over `[a, b]` is the difference of the values at `b` and `a`, so accruing over
+    /// `[a, b]` gives the same total no matter how many checkpoints it is split into.
+    pub fn cumulative_multiplier_unsafe(&self, ts: i64) -> Option<i128> {
+        let points = self.active_points();
+        let first = points.first()?;
+        if ts <= first.ts {
+            return i128::from(ts.checked_sub(first.ts)?)
+                .checked_mul(first.multiplier_bps.into())?
+                .checked_mul(2);
+        }
+
+        let mut cumulative: i128 = 0;
+        for pair in points.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            let length = i128::from(end.ts.checked_sub(start.ts)?);
+            let start_multiplier = i128::from(start.multiplier_bps);
+            let end_multiplier = i128::from(end.multiplier_bps);
+            if ts >= end.ts {
+                // whole segment
+                cumulative = cumulative.checked_add(
+                    length.checked_mul(start_multiplier.checked_add(end_multiplier)?)?,
+                )?;
+                continue;
+            }
+
+            // partial segment: elapsed * (2 * m_start * length + (m_end - m_start) * elapsed) / length
+            // The numerator cannot be negative since the multiplier is never negative.
+            let elapsed = i128::from(ts.checked_sub(start.ts)?);
+            let numerator = elapsed.checked_mul(
+                start_multiplier
+                    .checked_mul(length)?
+                    .checked_mul(2)?
+                    .checked_add(
+                        end_multiplier
+                            .checked_sub(start_multiplier)?
+                            .checked_mul(elapsed)?,
+                    )?,
+            )?;
+            return cumulative.checked_add(numerator.checked_div(length)?);
+        }
+
+        let last = points.last()?;
+        cumulative.checked_add(
+            i128::from(ts.checked_sub(last.ts)?)
+                .checked_mul(last.multiplier_bps.into())?
+                .checked_mul(2)?,
+        )
+    }
+
+    /// Returns true if `other` has the same multiplier as this schedule at every time up to `ts`,
+    /// so that replacing this schedule with `other` does not change any rewards already accrued.
+    pub fn agrees_until_unsafe(&self, other: &EmissionSchedule, ts: i64) -> Option<bool> {
+        // Both multipliers are linear between the timestamps of their points,
+        // so they agree if they agree on both sides of each of these timestamps.
+        let timestamps = self
+            .active_points()
+            .iter()
+            .chain(other.active_points())
+            .map(|point| point.ts)
+            .filter(|point_ts| *point_ts < ts)
+            .chain(std::iter::once(ts));
+        for point_ts in timestamps {
+            let (left, right) = self.multiplier_limits_unsafe(point_ts)?;
+            let (other_left, other_right) = other.multiplier_limits_unsafe(point_ts)?;
+            if !fractions_equal_unsafe(left, other_left)?
+                || (point_ts < ts && !fractions_equal_unsafe(right, other_right)?)
+            {
+                return Some(false);
+            }
+        }
+        Some(true)
+    }
+
+    /// Computes the exact multiplier just before and at `ts`, in BPS,
+    /// as `(numerator, denominator)` fractions.
+    fn multiplier_limits_unsafe(&self, ts: i64) -> Option<((i128, i128), (i128, i128))> {
+        let points = self.active_points();
+        let (first, last) = match (points.first(), points.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Some(((MAX_BPS.into(), 1), (MAX_BPS.into(), 1))),
+        };
+        let segment_value = |start: &EmissionPoint, end: &EmissionPoint| {
+            let length = i128::from(end.ts.checked_sub(start.ts)?);
+            let elapsed = i128::from(ts.checked_sub(start.ts)?);
+            let delta = i128::from(end.multiplier_bps).checked_sub(start.multiplier_bps.into())?;
+            Some((
+                i128::from(start.multiplier_bps)
+                    .checked_mul(length)?
+                    .checked_add(delta.checked_mul(elapsed)?)?,
+                length,
+            ))
+        };
+
+        let mut left = (i128::from(last.multiplier_bps), 1);
+        let mut right = left;
+        if ts <= first.ts {
+            left = (first.multiplier_bps.into(), 1);
+        }
+        if ts < first.ts {
+            right = left;
+        }
+        for pair in points.windows(2) {
+            let (start, end) = (&pair[0], &pair[1]);
+            if start.ts < ts && ts <= end.ts {
+                left = segment_value(start, end)?;
+            }
+            if start.ts <= ts && ts < end.ts {
+                right = segment_value(start, end)?;
+            }
+        }
+        Some((left, right))
+    }
+}
+
+/// Returns true if two `(numerator, denominator)` fractions with positive denominators are equal.
+fn fractions_equal_unsafe(a: (i128, i128), b: (i128, i128)) -> Option<bool> {
+    Some(a.0.checked_mul(b.1)? == b.0.checked_mul(a.1)?)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const SECONDS_PER_DAY: i64 = 86_400;
+
+    fn point(ts: i64, multiplier_bps: u64) -> EmissionPoint {
+        EmissionPoint { ts, multiplier_bps }
+    }
+
+    /// Halves every 180 days twice, then decays linearly to zero over 360 days.
+    fn halvings_then_decay() -> EmissionSchedule {
+        let halving = 180 * SECONDS_PER_DAY;
+        EmissionSchedule::try_from_points(&[
+            point(0, MAX_BPS),
+            point(halving, MAX_BPS),
+            point(halving, MAX_BPS / 2),
+            point(halving * 2, MAX_BPS / 2),
+            point(halving * 2, MAX_BPS / 4),
+            point(halving * 4, 0),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(EmissionSchedule::try_from_points(&[]).unwrap().is_empty());
+        // unsorted
+        assert!(EmissionSchedule::try_from_points(&[point(2, 0), point(1, 0)]).is_err());
+        // more than two points at the same timestamp
+        assert!(
+            EmissionSchedule::try_from_points(&[point(1, 0), point(1, 1), point(1, 2)]).is_err()
+        );
+        // multiplier above 100%
+        assert!(EmissionSchedule::try_from_points(&[point(1, MAX_BPS + 1)]).is_err());
+        // too many points
+        let points: Vec<EmissionPoint> = (0..=MAX_EMISSION_POINTS as i64)
+            .map(|i| point(i, 0))
+            .collect();
+        assert!(EmissionSchedule::try_from_points(&points).is_err());
+    }
+
+    #[test]
+    fn test_halvings_then_decay() {
+        let schedule = halvings_then_decay();
+        let halving = 180 * SECONDS_PER_DAY;
+
+        assert_eq!(schedule.multiplier_at_unsafe(-1).unwrap(), MAX_BPS);
+        assert_eq!(schedule.multiplier_at_unsafe(halving - 1).unwrap(), MAX_BPS);
+        assert_eq!(schedule.multiplier_at_unsafe(halving).unwrap(), MAX_BPS / 2);
+        assert_eq!(
+            schedule.multiplier_at_unsafe(halving * 2).unwrap(),
+            MAX_BPS / 4
+        );
+        assert_eq!(
+            schedule.multiplier_at_unsafe(halving * 3).unwrap(),
+            MAX_BPS / 8
+        );
+        assert_eq!(schedule.multiplier_at_unsafe(halving * 4).unwrap(), 0);
+        assert_eq!(schedule.multiplier_at_unsafe(i64::MAX).unwrap(), 0);
+
+        let full = i128::from(halving) * i128::from(MAX_BPS) * 2;
+        assert_eq!(schedule.cumulative_multiplier_unsafe(0).unwrap(), 0);
+        assert_eq!(
+            schedule.cumulative_multiplier_unsafe(halving).unwrap(),
+            full
+        );
+        assert_eq!(
+            schedule.cumulative_multiplier_unsafe(halving * 2).unwrap(),
+            full + full / 2
+        );
+        // the linear decay emits as much as half of its duration at its starting rate
+        assert_eq!(
+            schedule.cumulative_multiplier_unsafe(halving * 4).unwrap(),
+            full + full / 2 + full / 4
+        );
+        assert_eq!(
+            schedule
+                .cumulative_multiplier_unsafe(halving * 100)
+                .unwrap(),
+            full + full / 2 + full / 4
+        );
+    }
+
+    #[test]
+    fn test_agrees_until() {
+        let halving = 180 * SECONDS_PER_DAY;
+        let schedule = halvings_then_decay();
+        let constant = EmissionSchedule::default();
+        assert!(schedule.agrees_until_unsafe(&schedule, i64::MAX).unwrap());
+
+        // a constant schedule agrees with one which starts at the full rate
+        assert!(constant.agrees_until_unsafe(&schedule, halving).unwrap());
+        assert!(!constant
+            .agrees_until_unsafe(&schedule, halving + 1)
+            .unwrap());
+
+        // the future may change once the current multiplier is pinned
+        let extended = EmissionSchedule::try_from_points(&[
+            point(0, MAX_BPS),
+            point(halving, MAX_BPS),
+            point(halving, MAX_BPS / 2),
+            point(halving * 2, MAX_BPS / 2),
+            point(halving * 2, MAX_BPS / 4),
+            point(halving * 3, MAX_BPS / 8),
+            point(halving * 3, MAX_BPS / 4),
+        ])
+        .unwrap();
+        assert!(schedule
+            .agrees_until_unsafe(&extended, halving * 3)
+            .unwrap());
+        assert!(!schedule
+            .agrees_until_unsafe(&extended, halving * 3 + 1)
+            .unwrap());
+
+        // moving the end of the decay changes its slope in the past
+        let slower = EmissionSchedule::try_from_points(&[
+            point(0, MAX_BPS),
+            point(halving, MAX_BPS),
+            point(halving, MAX_BPS / 2),
+            point(halving * 2, MAX_BPS / 2),
+            point(halving * 2, MAX_BPS / 4),
+            point(halving * 5, 0),
+        ])
+        .unwrap();
+        assert!(schedule.agrees_until_unsafe(&slower, halving * 2).unwrap());
+        assert!(!schedule
+            .agrees_until_unsafe(&slower, halving * 2 + 1)
+            .unwrap());
+
+        // collinear points do not change the multiplier
+        let split = EmissionSchedule::try_from_points(&[
+            point(0, MAX_BPS),
+            point(halving, MAX_BPS),
+            point(halving, MAX_BPS / 2),
+            point(halving * 2, MAX_BPS / 2),
+            point(halving * 2, MAX_BPS / 4),
+            point(halving * 3, MAX_BPS / 8),
+            point(halving * 4, 0),
+        ])
+        .unwrap();
+        assert!(schedule.agrees_until_unsafe(&split, i64::MAX).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn test_cumulative_multiplier_is_monotonic(
+            a in -SECONDS_PER_DAY..(800 * SECONDS_PER_DAY),
+            b in -SECONDS_PER_DAY..(800 * SECONDS_PER_DAY),
+        ) {
+            let schedule = halvings_then_decay();
+            let (a, b) = if a <= b { (a, b) } else { (b, a) };
+            let integral = schedule.cumulative_multiplier_unsafe(b).unwrap()
+                - schedule.cumulative_multiplier_unsafe(a).unwrap();
+            prop_assert!(integral >= 0);
+            // the multiplier never exceeds 100%
+            prop_assert!(integral <= i128::from(b - a) * i128::from(MAX_BPS) * 2);
+        }
+    }
+}
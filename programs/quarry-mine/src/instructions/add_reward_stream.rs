@@ -0,0 +1,94 @@
+//! Implementation of the [crate::quarry_mine::add_reward_stream] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::add_reward_stream] instruction.
+pub fn handler(ctx: Context<AddRewardStream>) -> Result<()> {
+    let mint_wrapper = ctx.accounts.mint_wrapper.key();
+    let quarry = &mut ctx.accounts.quarry;
+    invariant!(
+        !quarry
+            .reward_streams
+            .iter()
+            .any(|stream| stream.mint_wrapper == mint_wrapper),
+        RewardStreamAlreadyExists
+    );
+    let index = unwrap_opt!(
+        quarry
+            .reward_streams
+            .iter()
+            .position(|stream| !stream.is_active()),
+        MaxRewardStreamsExceeded
+    );
+
+    let current_ts = Clock::get()?.unix_timestamp;
+    quarry.reward_streams[index] = RewardStream {
+        mint_wrapper,
+        rewards_token_mint: ctx.accounts.rewards_token_mint.key(),
+        annual_rewards_rate: 0,
+        famine_ts: i64::MAX,
+        last_update_ts: current_ts,
+        rewards_per_token_stored: 0,
+    };
+
+    emit!(RewardStreamAddEvent {
+        quarry: quarry.key(),
+        index: index as u8,
+        rewards_token_mint: ctx.accounts.rewards_token_mint.key(),
+        timestamp: current_ts,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::add_reward_stream].
+#[derive(Accounts)]
+pub struct AddRewardStream<'info> {
+    /// [Rewarder] of the [Quarry].
+    pub auth: ReadOnlyRewarderWithAuthority<'info>,
+
+    /// [Quarry] to add the stream to.
+    #[account(mut)]
+    pub quarry: Account<'info, Quarry>,
+
+    /// Mint wrapper which mints the stream's rewards.
+    pub mint_wrapper: Account<'info, quarry_mint_wrapper::MintWrapper>,
+
+    /// [quarry_mint_wrapper::Minter] of the [Rewarder] on the mint wrapper.
+    pub minter: Account<'info, quarry_mint_wrapper::Minter>,
+
+    /// Mint of the stream's rewards token.
+    pub rewards_token_mint: Account<'info, Mint>,
+}
+
+impl<'info> Validate<'info> for AddRewardStream<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.quarry.rewarder, self.auth.rewarder);
+        self.auth.rewarder.assert_not_paused()?;
+        self.auth.validate()?;
+
+        assert_keys_eq!(self.mint_wrapper.token_mint, self.rewards_token_mint);
+        assert_keys_eq!(
+            self.rewards_token_mint.mint_authority.unwrap(),
+            self.mint_wrapper
+        );
+
+        assert_keys_eq!(self.minter.mint_wrapper, self.mint_wrapper);
+        assert_keys_eq!(self.minter.minter_authority, self.auth.rewarder);
+        Ok(())
+    }
+}
+
+/// Emitted when a [RewardStream] is added to a [Quarry].
+#[event]
+pub struct RewardStreamAddEvent {
+    /// The [Quarry].
+    #[index]
+    pub quarry: Pubkey,
+    /// Index of the stream in [Quarry::reward_streams].
+    pub index: u8,
+    /// Mint of the stream's rewards token.
+    pub rewards_token_mint: Pubkey,
+    /// When the event took place.
+    pub timestamp: i64,
+}
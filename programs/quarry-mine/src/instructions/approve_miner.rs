@@ -0,0 +1,32 @@
+//! Implementation of the [crate::quarry_mine::approve_miner] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::approve_miner] instruction.
+pub fn handler(ctx: Context<ApproveMiner>, proof: &[[u8; 32]]) -> Result<()> {
+    ctx.accounts
+        .quarry
+        .approve_miner(&mut ctx.accounts.miner, proof)
+}
+
+/// Accounts for [quarry_mine::approve_miner].
+#[derive(Accounts)]
+pub struct ApproveMiner<'info> {
+    /// Authority of the [Miner].
+    pub authority: Signer<'info>,
+
+    /// [Miner] to approve.
+    #[account(mut)]
+    pub miner: Account<'info, Miner>,
+
+    /// [Quarry] of the [Miner].
+    pub quarry: Account<'info, Quarry>,
+}
+
+impl<'info> Validate<'info> for ApproveMiner<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.miner.authority, self.authority);
+        assert_keys_eq!(self.miner.quarry, self.quarry);
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+//! Implementation of the [crate::quarry_mine::claim_and_restake] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::claim_and_restake] instruction.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, ClaimAndRestake<'info>>) -> Result<()> {
+    let claim = &mut ctx.accounts.claim_v2.claim;
+    let now = Clock::get()?.unix_timestamp;
+    claim
+        .quarry
+        .update_rewards_and_miner(&mut claim.miner, &claim.rewarder, now)?;
+
+    // Claim the rewards into the vault of the [Miner] staking them
+    let vault_balance_before = ctx.accounts.claim_v2.rewards_token_account.amount;
+    ctx.accounts.claim_v2.calculate_and_claim_rewards()?;
+    ctx.accounts.claim_v2.rewards_token_account.reload()?;
+    let amount = unwrap_int!(ctx
+        .accounts
+        .claim_v2
+        .rewards_token_account
+        .amount
+        .checked_sub(vault_balance_before));
+    if amount == 0 {
+        return Ok(());
+    }
+
+    ctx.accounts.stake_quarry.process_stake_action_internal(
+        StakeAction::Stake,
+        now,
+        &ctx.accounts.claim_v2.claim.rewarder,
+        &mut ctx.accounts.stake_miner,
+        amount,
+    )?;
+    checkpoints::record_balance_checkpoint(&ctx.accounts.stake_miner, ctx.remaining_accounts, now)?;
+
+    emit!(StakeEvent {
+        timestamp: now,
+        authority: ctx.accounts.stake_miner.authority,
+        amount,
+        token: ctx.accounts.stake_quarry.token_mint_key,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::claim_and_restake].
+#[derive(Accounts)]
+pub struct ClaimAndRestake<'info> {
+    /// Accounts of [quarry_mine::claim_rewards_v2].
+    /// The rewards are claimed into the vault of the `stake_miner`.
+    pub claim_v2: ClaimRewardsV2<'info>,
+
+    /// [Miner] of the same authority staking the rewards token.
+    #[account(mut)]
+    pub stake_miner: Box<Account<'info, Miner>>,
+
+    /// [Quarry] of the rewards token.
+    #[account(mut)]
+    pub stake_quarry: Box<Account<'info, Quarry>>,
+}
+
+impl<'info> Validate<'info> for ClaimAndRestake<'info> {
+    fn validate(&self) -> Result<()> {
+        self.claim_v2.validate()?;
+        let rewarder = &self.claim_v2.claim.rewarder;
+        self.stake_quarry
+            .assert_not_paused(rewarder, PAUSE_SCOPE_STAKE)?;
+        assert_keys_eq!(self.stake_quarry.rewarder, rewarder.key());
+        assert_keys_neq!(self.stake_quarry, self.claim_v2.claim.quarry);
+        assert_keys_eq!(
+            self.stake_quarry.token_mint_key,
+            self.claim_v2.rewards_token_mint
+        );
+
+        assert_keys_eq!(self.stake_miner.authority, self.claim_v2.claim.authority);
+        assert_keys_eq!(self.stake_miner.quarry, self.stake_quarry);
+        assert_keys_eq!(
+            self.stake_miner.token_vault_key,
+            self.claim_v2.rewards_token_account
+        );
+        assert_keys_eq!(self.claim_v2.rewards_token_account.owner, self.stake_miner);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+//! Implementation of the [crate::quarry_mine::claim_early_exit_penalties] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::claim_early_exit_penalties] instruction.
+pub fn handler(ctx: Context<ClaimEarlyExitPenalties>) -> Result<()> {
+    let current_ts = Clock::get()?.unix_timestamp;
+    let claim = &mut ctx.accounts.claim;
+    claim
+        .quarry
+        .update_rewards_and_miner(&mut claim.miner, &claim.rewarder, current_ts)?;
+
+    let amount = claim.miner.penalties_earned;
+    if amount == 0 {
+        return Ok(());
+    }
+    claim.miner.penalties_earned = 0;
+
+    let seeds = gen_quarry_signer_seeds!(claim.quarry);
+    let signer_seeds = &[&seeds[..]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            claim.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.penalty_vault.to_account_info(),
+                to: ctx.accounts.token_account.to_account_info(),
+                authority: claim.quarry.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    emit!(EarlyExitPenaltiesClaimEvent {
+        authority: claim.authority.key(),
+        quarry: claim.quarry.key(),
+        amount,
+        timestamp: current_ts,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::claim_early_exit_penalties].
+#[derive(Accounts)]
+pub struct ClaimEarlyExitPenalties<'info> {
+    /// Claim accounts
+    pub claim: UserClaimV2<'info>,
+
+    /// [Quarry::early_exit_penalty_vault].
+    #[account(mut)]
+    pub penalty_vault: Account<'info, TokenAccount>,
+
+    /// Account receiving the penalties, in the staked token.
+    #[account(mut)]
+    pub token_account: Account<'info, TokenAccount>,
+}
+
+impl<'info> Validate<'info> for ClaimEarlyExitPenalties<'info> {
+    fn validate(&self) -> Result<()> {
+        self.claim.validate()?;
+        assert_keys_eq!(
+            self.penalty_vault,
+            self.claim.quarry.early_exit_penalty_vault
+        );
+        assert_keys_eq!(self.token_account.mint, self.claim.quarry.token_mint_key);
+        Ok(())
+    }
+}
+
+/// Emitted when redistributed early exit penalties are claimed.
+#[event]
+pub struct EarlyExitPenaltiesClaimEvent {
+    /// Authority of the [Miner].
+    #[index]
+    pub authority: Pubkey,
+    /// The [Quarry].
+    #[index]
+    pub quarry: Pubkey,
+    /// Staked tokens claimed.
+    pub amount: u64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
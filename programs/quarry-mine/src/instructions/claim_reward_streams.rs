@@ -0,0 +1,109 @@
+//! Implementation of the [crate::quarry_mine::claim_reward_streams] instruction.
+
+use crate::*;
+
+/// Number of remaining accounts supplied per [RewardStream] claimed.
+pub const ACCOUNTS_PER_REWARD_STREAM: usize = 4;
+
+/// Handler for the [crate::quarry_mine::claim_reward_streams] instruction.
+///
+/// The remaining accounts are groups of `[mint_wrapper, minter, rewards_token_mint, rewards_token_account]`,
+/// one group per stream to claim.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, ClaimRewardStreams<'info>>) -> Result<()> {
+    let streams = ctx
+        .remaining_accounts
+        .chunks_exact(ACCOUNTS_PER_REWARD_STREAM);
+    invariant!(streams.remainder().is_empty(), InvalidRemainingAccounts);
+
+    let now = Clock::get()?.unix_timestamp;
+    let claim = &mut ctx.accounts.claim;
+    claim
+        .quarry
+        .update_rewards_and_miner(&mut claim.miner, &claim.rewarder, now)?;
+
+    for accounts in streams {
+        ctx.accounts.claim_stream(accounts, now)?;
+    }
+
+    Ok(())
+}
+
+impl<'info> ClaimRewardStreams<'info> {
+    /// Claims the rewards of the stream minted by the given accounts.
+    fn claim_stream(&mut self, accounts: &[AccountInfo<'info>], now: i64) -> Result<()> {
+        let mint_wrapper: Account<quarry_mint_wrapper::MintWrapper> =
+            Account::try_from(&accounts[0])?;
+        let minter: Account<quarry_mint_wrapper::Minter> = Account::try_from(&accounts[1])?;
+        let rewards_token_mint: Account<Mint> = Account::try_from(&accounts[2])?;
+        let rewards_token_account: Account<TokenAccount> = Account::try_from(&accounts[3])?;
+
+        let index =
+            unwrap_opt!(
+                self.claim.quarry.reward_streams.iter().position(
+                    |stream| stream.is_active() && stream.mint_wrapper == mint_wrapper.key()
+                ),
+                RewardStreamNotFound
+            );
+        let stream = self.claim.quarry.reward_streams[index];
+        assert_keys_eq!(stream.rewards_token_mint, rewards_token_mint);
+        assert_keys_eq!(mint_wrapper.token_mint, rewards_token_mint);
+        assert_keys_eq!(minter.mint_wrapper, mint_wrapper);
+        assert_keys_eq!(minter.minter_authority, self.claim.rewarder);
+        assert_keys_eq!(rewards_token_account.mint, rewards_token_mint);
+
+        let miner_stream = &mut self.claim.miner.reward_streams[index];
+        let amount = miner_stream.rewards_earned;
+        if amount == 0 {
+            return Ok(());
+        }
+        miner_stream.rewards_earned = 0;
+
+        let seeds = gen_rewarder_signer_seeds!(self.claim.rewarder);
+        let signer_seeds = &[&seeds[..]];
+        quarry_mint_wrapper::cpi::perform_mint(
+            CpiContext::new_with_signer(
+                self.mint_wrapper_program.to_account_info(),
+                quarry_mint_wrapper::cpi::accounts::PerformMint {
+                    mint_wrapper: mint_wrapper.to_account_info(),
+                    minter_authority: self.claim.rewarder.to_account_info(),
+                    token_mint: rewards_token_mint.to_account_info(),
+                    destination: rewards_token_account.to_account_info(),
+                    minter: minter.to_account_info(),
+                    token_program: self.claim.token_program.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        emit!(ClaimEvent {
+            authority: self.claim.authority.key(),
+            staked_token: self.claim.quarry.token_mint_key,
+            timestamp: now,
+            rewards_token: rewards_token_mint.key(),
+            amount,
+            fees: 0,
+            referrer: Pubkey::default(),
+            referral_fees: 0,
+        });
+
+        Ok(())
+    }
+}
+
+/// Accounts for [quarry_mine::claim_reward_streams].
+#[derive(Accounts)]
+pub struct ClaimRewardStreams<'info> {
+    /// Mint wrapper program.
+    pub mint_wrapper_program: Program<'info, quarry_mint_wrapper::program::QuarryMintWrapper>,
+
+    /// Claim accounts
+    pub claim: UserClaimV2<'info>,
+}
+
+impl<'info> Validate<'info> for ClaimRewardStreams<'info> {
+    fn validate(&self) -> Result<()> {
+        self.claim.validate()?;
+        Ok(())
+    }
+}
@@ -15,29 +15,22 @@ pub fn handler(ctx: Context<ClaimRewards>) -> Result<()> {
 impl<'info> ClaimRewards<'info> {
     /// Calculates rewards and claims them.
     pub fn calculate_and_claim_rewards(&mut self) -> Result<()> {
-        let miner = &mut self.claim.miner;
-        let amount_claimable = miner.rewards_earned;
+        let amount_claimable = self.claim.miner.rewards_earned;
         if amount_claimable == 0 {
             // 0 claimable -- skip all logic
             return Ok(());
         }
 
         // Calculate rewards
-        let max_claim_fee_millibps = self.claim.rewarder.max_claim_fee_millibps;
-        invariant!(
-            max_claim_fee_millibps < MAX_BPS * DEFAULT_CLAIM_FEE_MILLIBPS,
-            InvalidMaxClaimFee
-        );
-        let max_claim_fee = unwrap_int!(::u128::mul_div_u64(
-            amount_claimable,
-            max_claim_fee_millibps,
-            MAX_BPS * DEFAULT_CLAIM_FEE_MILLIBPS
-        ));
+        let max_claim_fee = self.claim.rewarder.compute_claim_fee(amount_claimable)?;
 
         let amount_claimable_minus_fees = unwrap_int!(amount_claimable.checked_sub(max_claim_fee));
 
         // Claim all rewards.
-        miner.rewards_earned = 0;
+        self.claim
+            .quarry
+            .claim_rewards_earned(&mut self.claim.miner)?;
+        self.claim.quarry.record_claim_fees(max_claim_fee)?;
 
         // Setup remaining variables
         self.mint_claimed_tokens(amount_claimable_minus_fees)?;
@@ -51,6 +44,8 @@ impl<'info> ClaimRewards<'info> {
             rewards_token: self.rewards_token_mint.key(),
             amount: amount_claimable_minus_fees,
             fees: max_claim_fee,
+            referrer: Pubkey::default(),
+            referral_fees: 0,
         });
 
         Ok(())
@@ -198,7 +193,8 @@ impl<'info> Validate<'info> for ClaimRewards<'info> {
 
 impl<'info> Validate<'info> for UserClaim<'info> {
     fn validate(&self) -> Result<()> {
-        invariant!(!self.rewarder.is_paused, Paused);
+        self.quarry
+            .assert_not_paused(&self.rewarder, PAUSE_SCOPE_CLAIM)?;
         // authority
         invariant!(self.authority.is_signer, Unauthorized);
         assert_keys_eq!(self.authority, self.miner.authority);
@@ -0,0 +1,129 @@
+//! Implementation of the [crate::quarry_mine::claim_rewards_from_vault] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::claim_rewards_from_vault] instruction.
+pub fn handler(ctx: Context<ClaimRewardsFromVault>) -> Result<()> {
+    ctx.accounts.claim_rewards(None)
+}
+
+impl<'info> ClaimRewardsFromVault<'info> {
+    /// Claims the rewards of the [Miner] from the vault, paying the referral fee out of the
+    /// claimed rewards to the referrer if provided.
+    pub fn claim_rewards(
+        &mut self,
+        referrer_token_account: Option<&Account<'info, TokenAccount>>,
+    ) -> Result<()> {
+        let claim = &mut self.claim;
+        let now = Clock::get()?.unix_timestamp;
+        claim
+            .quarry
+            .update_rewards_and_miner(&mut claim.miner, &claim.rewarder, now)?;
+
+        let amount_claimable = claim.quarry.claim_rewards_earned(&mut claim.miner)?;
+        if amount_claimable == 0 {
+            // 0 claimable -- skip all logic
+            return Ok(());
+        }
+
+        let claim_fee = claim.rewarder.compute_claim_fee(amount_claimable)?;
+        let referral_fee = match referrer_token_account {
+            Some(_) => claim.rewarder.compute_referral_fee(amount_claimable)?,
+            None => 0,
+        };
+        let amount_claimable_minus_fees = unwrap_int!(amount_claimable
+            .checked_sub(claim_fee)
+            .and_then(|v| v.checked_sub(referral_fee)));
+        claim
+            .quarry
+            .record_claim_fees(unwrap_int!(claim_fee.checked_add(referral_fee)))?;
+
+        self.transfer_from_vault(
+            self.rewards_token_account.to_account_info(),
+            amount_claimable_minus_fees,
+        )?;
+        self.transfer_from_vault(self.claim_fee_token_account.to_account_info(), claim_fee)?;
+        if let Some(referrer_token_account) = referrer_token_account {
+            self.transfer_from_vault(referrer_token_account.to_account_info(), referral_fee)?;
+        }
+
+        emit!(ClaimEvent {
+            authority: self.claim.authority.key(),
+            staked_token: self.claim.quarry.token_mint_key,
+            timestamp: now,
+            rewards_token: self.rewards_token_account.mint,
+            amount: amount_claimable_minus_fees,
+            fees: claim_fee,
+            referrer: referrer_token_account
+                .map(|account| account.key())
+                .unwrap_or_default(),
+            referral_fees: referral_fee,
+        });
+
+        Ok(())
+    }
+
+    /// Transfers rewards out of the [Rewarder::rewards_vault], signing as the [Rewarder].
+    fn transfer_from_vault(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let seeds = gen_rewarder_signer_seeds!(self.claim.rewarder);
+        let signer_seeds = &[&seeds[..]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.claim.token_program.to_account_info(),
+                Transfer {
+                    from: self.rewards_vault.to_account_info(),
+                    to,
+                    authority: self.claim.rewarder.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+}
+
+/// Accounts for [quarry_mine::claim_rewards_from_vault].
+#[derive(Accounts)]
+pub struct ClaimRewardsFromVault<'info> {
+    /// The [Rewarder::rewards_vault].
+    #[account(mut)]
+    pub rewards_vault: Box<Account<'info, TokenAccount>>,
+
+    /// Account to claim rewards for.
+    #[account(mut)]
+    pub rewards_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Account to send claim fees to.
+    #[account(mut)]
+    pub claim_fee_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Claim accounts
+    pub claim: UserClaimV2<'info>,
+}
+
+impl<'info> Validate<'info> for ClaimRewardsFromVault<'info> {
+    fn validate(&self) -> Result<()> {
+        self.claim.validate()?;
+        self.claim.rewarder.assert_not_paused()?;
+
+        invariant!(self.claim.rewarder.is_vault_funded());
+        assert_keys_eq!(self.rewards_vault, self.claim.rewarder.rewards_vault);
+
+        // rewards_token_account validate
+        assert_keys_eq!(
+            self.rewards_token_account.mint,
+            self.claim.rewarder.rewards_token_mint
+        );
+
+        // claim_fee_token_account validate
+        assert_keys_eq!(
+            self.claim_fee_token_account,
+            self.claim.rewarder.claim_fee_token_account
+        );
+
+        Ok(())
+    }
+}
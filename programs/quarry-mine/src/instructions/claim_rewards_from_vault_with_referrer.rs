@@ -0,0 +1,37 @@
+//! Implementation of the [crate::quarry_mine::claim_rewards_from_vault_with_referrer] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::claim_rewards_from_vault_with_referrer] instruction.
+pub fn handler(ctx: Context<ClaimRewardsFromVaultWithReferrer>) -> Result<()> {
+    ctx.accounts
+        .claim
+        .claim_rewards(Some(&ctx.accounts.referrer_token_account))
+}
+
+/// Accounts for [quarry_mine::claim_rewards_from_vault_with_referrer].
+#[derive(Accounts)]
+pub struct ClaimRewardsFromVaultWithReferrer<'info> {
+    /// Accounts of [quarry_mine::claim_rewards_from_vault].
+    pub claim: ClaimRewardsFromVault<'info>,
+
+    /// Account to send referral fees to.
+    #[account(mut)]
+    pub referrer_token_account: Box<Account<'info, TokenAccount>>,
+}
+
+impl<'info> Validate<'info> for ClaimRewardsFromVaultWithReferrer<'info> {
+    fn validate(&self) -> Result<()> {
+        self.claim.validate()?;
+        assert_keys_eq!(
+            self.referrer_token_account.mint,
+            self.claim.claim.rewarder.rewards_token_mint
+        );
+        assert_keys_neq!(
+            self.referrer_token_account.owner,
+            self.claim.claim.authority,
+            InvalidReferrer
+        );
+        Ok(())
+    }
+}
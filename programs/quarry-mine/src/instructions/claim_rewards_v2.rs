@@ -15,33 +15,46 @@ pub fn handler(ctx: Context<ClaimRewardsV2>) -> Result<()> {
 impl<'info> ClaimRewardsV2<'info> {
     /// Calculates rewards and claims them.
     pub fn calculate_and_claim_rewards(&mut self) -> Result<()> {
-        let miner = &mut self.claim.miner;
-        let amount_claimable = miner.rewards_earned;
+        self.calculate_and_claim_rewards_with_referrer(None)
+    }
+
+    /// Calculates rewards and claims them, paying the referral fee out of the claimed
+    /// rewards to the referrer if provided.
+    pub fn calculate_and_claim_rewards_with_referrer(
+        &mut self,
+        referrer_token_account: Option<&Account<'info, TokenAccount>>,
+    ) -> Result<()> {
+        let amount_claimable = self.claim.miner.rewards_earned;
         if amount_claimable == 0 {
             // 0 claimable -- skip all logic
             return Ok(());
         }
 
         // Calculate rewards
-        let max_claim_fee_millibps = self.claim.rewarder.max_claim_fee_millibps;
-        invariant!(
-            max_claim_fee_millibps < MAX_BPS * DEFAULT_CLAIM_FEE_MILLIBPS,
-            InvalidMaxClaimFee
-        );
-        let max_claim_fee = unwrap_int!(::u128::mul_div_u64(
-            amount_claimable,
-            max_claim_fee_millibps,
-            MAX_BPS * DEFAULT_CLAIM_FEE_MILLIBPS
-        ));
+        let max_claim_fee = self.claim.rewarder.compute_claim_fee(amount_claimable)?;
+        let referral_fee = match referrer_token_account {
+            Some(_) => self.claim.rewarder.compute_referral_fee(amount_claimable)?,
+            None => 0,
+        };
 
-        let amount_claimable_minus_fees = unwrap_int!(amount_claimable.checked_sub(max_claim_fee));
+        let amount_claimable_minus_fees = unwrap_int!(amount_claimable
+            .checked_sub(max_claim_fee)
+            .and_then(|v| v.checked_sub(referral_fee)));
 
         // Claim all rewards.
-        miner.rewards_earned = 0;
+        self.claim
+            .quarry
+            .claim_rewards_earned(&mut self.claim.miner)?;
+        self.claim
+            .quarry
+            .record_claim_fees(unwrap_int!(max_claim_fee.checked_add(referral_fee)))?;
 
         // Setup remaining variables
         self.mint_claimed_tokens(amount_claimable_minus_fees)?;
         self.mint_fees(max_claim_fee)?;
+        if let Some(referrer_token_account) = referrer_token_account {
+            self.perform_mint(referrer_token_account, referral_fee)?;
+        }
 
         let now = Clock::get()?.unix_timestamp;
         emit!(ClaimEvent {
@@ -51,6 +64,10 @@ impl<'info> ClaimRewardsV2<'info> {
             rewards_token: self.rewards_token_mint.key(),
             amount: amount_claimable_minus_fees,
             fees: max_claim_fee,
+            referrer: referrer_token_account
+                .map(|account| account.key())
+                .unwrap_or_default(),
+            referral_fees: referral_fee,
         });
 
         Ok(())
@@ -190,7 +207,8 @@ impl<'info> Validate<'info> for ClaimRewardsV2<'info> {
 
 impl<'info> Validate<'info> for UserClaimV2<'info> {
     fn validate(&self) -> Result<()> {
-        invariant!(!self.rewarder.is_paused, Paused);
+        self.quarry
+            .assert_not_paused(&self.rewarder, PAUSE_SCOPE_CLAIM)?;
         // authority
         invariant!(self.authority.is_signer, Unauthorized);
         assert_keys_eq!(self.authority, self.miner.authority);
@@ -218,8 +236,13 @@ pub struct ClaimEvent {
     pub rewards_token: Pubkey,
     /// Amount of rewards token received.
     pub amount: u64,
-    /// Fees paid.
+    /// Fees paid to the [Rewarder::claim_fee_token_account].
     pub fees: u64,
     /// When the event occurred.
     pub timestamp: i64,
+    /// [TokenAccount] which received the referral fee.
+    /// If default, there was no referrer.
+    pub referrer: Pubkey,
+    /// Share of the claim fee paid to the referrer.
+    pub referral_fees: u64,
 }
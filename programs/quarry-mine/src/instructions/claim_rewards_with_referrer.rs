@@ -0,0 +1,45 @@
+//! Implementation of the [crate::quarry_mine::claim_rewards_with_referrer] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::claim_rewards_with_referrer] instruction.
+pub fn handler(ctx: Context<ClaimRewardsWithReferrer>) -> Result<()> {
+    let claim = &mut ctx.accounts.claim_v2.claim;
+    let now = Clock::get()?.unix_timestamp;
+    claim
+        .quarry
+        .update_rewards_and_miner(&mut claim.miner, &claim.rewarder, now)?;
+
+    ctx.accounts
+        .claim_v2
+        .calculate_and_claim_rewards_with_referrer(Some(&ctx.accounts.referrer_token_account))?;
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::claim_rewards_with_referrer].
+#[derive(Accounts)]
+pub struct ClaimRewardsWithReferrer<'info> {
+    /// Accounts of [quarry_mine::claim_rewards_v2].
+    pub claim_v2: ClaimRewardsV2<'info>,
+
+    /// Account to send referral fees to.
+    #[account(mut)]
+    pub referrer_token_account: Box<Account<'info, TokenAccount>>,
+}
+
+impl<'info> Validate<'info> for ClaimRewardsWithReferrer<'info> {
+    fn validate(&self) -> Result<()> {
+        self.claim_v2.validate()?;
+        assert_keys_eq!(
+            self.referrer_token_account.mint,
+            self.claim_v2.rewards_token_mint
+        );
+        assert_keys_neq!(
+            self.referrer_token_account.owner,
+            self.claim_v2.claim.authority,
+            InvalidReferrer
+        );
+        Ok(())
+    }
+}
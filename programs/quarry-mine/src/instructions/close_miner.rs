@@ -0,0 +1,139 @@
+//! Implementation of the [crate::quarry_mine::close_miner] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::close_miner] instruction.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CloseMiner<'info>>) -> Result<()> {
+    let seeds = gen_miner_signer_seeds!(ctx.accounts.miner);
+    let signer_seeds = &[&seeds[..]];
+
+    // Return any tokens sent directly to the vault to the authority.
+    let surplus = ctx.accounts.miner_vault.amount;
+    if surplus > 0 {
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.miner_vault.to_account_info(),
+                    to: ctx.accounts.token_account.to_account_info(),
+                    authority: ctx.accounts.miner.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            surplus,
+        )?;
+    }
+
+    // Close the checkpoints so that the [Miner] may be recreated later.
+    let miner_checkpoints = ctx.accounts.miner.checkpoints;
+    if miner_checkpoints != Pubkey::default() {
+        let info = unwrap_opt!(
+            ctx.remaining_accounts
+                .iter()
+                .find(|info| *info.key == miner_checkpoints),
+            MissingMinerCheckpoints
+        );
+        invariant!(info.is_writable, MissingMinerCheckpoints);
+        let checkpoints: Account<MinerCheckpoints> = Account::try_from(info)?;
+        anchor_lang::AccountsClose::close(
+            &checkpoints,
+            ctx.accounts.rent_recipient.to_account_info(),
+        )?;
+    }
+
+    // Close the vault, then the miner.
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        token::CloseAccount {
+            account: ctx.accounts.miner_vault.to_account_info(),
+            destination: ctx.accounts.rent_recipient.to_account_info(),
+            authority: ctx.accounts.miner.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+
+    let quarry = &mut ctx.accounts.quarry;
+    quarry.num_closed_miners = unwrap_int!(quarry.num_closed_miners.checked_add(1));
+
+    emit!(MinerCloseEvent {
+        authority: ctx.accounts.authority.key(),
+        quarry: quarry.key(),
+        miner: ctx.accounts.miner.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::close_miner].
+#[derive(Accounts)]
+pub struct CloseMiner<'info> {
+    /// Miner authority (i.e. the user).
+    pub authority: Signer<'info>,
+
+    /// [Miner] to close.
+    #[account(mut, close = rent_recipient)]
+    pub miner: Account<'info, Miner>,
+
+    /// Vault of the [Miner].
+    #[account(mut)]
+    pub miner_vault: Account<'info, TokenAccount>,
+
+    /// Token account of the authority which receives any tokens left in the vault.
+    #[account(mut)]
+    pub token_account: Account<'info, TokenAccount>,
+
+    /// [Quarry] of the [Miner].
+    #[account(mut)]
+    pub quarry: Account<'info, Quarry>,
+
+    /// Receives the rent of the [Miner] and its vault.
+    /// CHECK: Arbitrary account.
+    #[account(mut)]
+    pub rent_recipient: UncheckedAccount<'info>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Validate<'info> for CloseMiner<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.authority, self.miner.authority);
+        assert_keys_eq!(self.miner.quarry, self.quarry);
+        assert_keys_eq!(self.miner.token_vault_key, self.miner_vault);
+        assert_keys_neq!(self.rent_recipient, self.miner);
+        assert_keys_neq!(self.rent_recipient, self.miner_vault);
+        assert_keys_neq!(self.token_account, self.miner_vault);
+        assert_keys_eq!(self.token_account.owner, self.authority);
+        assert_keys_eq!(self.token_account.mint, self.quarry.token_mint_key);
+
+        let miner = &self.miner;
+        invariant!(
+            miner.balance == 0
+                && miner.rewards_earned == 0
+                && miner.unbonding_balance == 0
+                && miner.penalties_earned == 0
+                && miner
+                    .reward_streams
+                    .iter()
+                    .all(|stream| stream.rewards_earned == 0),
+            MinerNotEmpty
+        );
+        Ok(())
+    }
+}
+
+/// Emitted when a [Miner] is closed.
+#[event]
+pub struct MinerCloseEvent {
+    /// Authority of the [Miner].
+    #[index]
+    pub authority: Pubkey,
+    /// [Quarry] of the [Miner].
+    #[index]
+    pub quarry: Pubkey,
+    /// The closed [Miner].
+    pub miner: Pubkey,
+    /// When the event took place.
+    pub timestamp: i64,
+}
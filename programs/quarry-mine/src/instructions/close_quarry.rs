@@ -0,0 +1,86 @@
+//! Implementation of the [crate::quarry_mine::close_quarry] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::close_quarry] instruction.
+///
+/// If the [Quarry] has an [Quarry::early_exit_penalty_vault], it must be supplied
+/// as the first remaining account.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CloseQuarry<'info>>) -> Result<()> {
+    let current_ts = Clock::get()?.unix_timestamp;
+    let rewarder = &mut ctx.accounts.auth.rewarder;
+    let quarry = &mut ctx.accounts.quarry;
+
+    // penalties left in the vault could no longer be claimed
+    if quarry.early_exit_penalty_vault != Pubkey::default() {
+        let penalty_vault: Account<TokenAccount> = Account::try_from(unwrap_opt!(
+            ctx.remaining_accounts.first(),
+            InvalidRemainingAccounts
+        ))?;
+        assert_keys_eq!(penalty_vault, quarry.early_exit_penalty_vault);
+        invariant!(penalty_vault.amount == 0, QuarryNotEmpty);
+    }
+
+    // report the final totals of the quarry before it stops being updated
+    let payroll = quarry.payroll(rewarder);
+    quarry.update_rewards_internal(current_ts, rewarder, &payroll)?;
+    quarry.sync_rewards_shares_generation(rewarder)?;
+    quarry.sync_rewards_epoch(rewarder)?;
+    quarry.report_rewards_totals(rewarder)?;
+    rewarder.retire_quarry(quarry)?;
+
+    emit!(QuarryCloseEvent {
+        rewarder: rewarder.key(),
+        token_mint: quarry.token_mint_key,
+        timestamp: current_ts,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::close_quarry].
+#[derive(Accounts)]
+pub struct CloseQuarry<'info> {
+    /// [Rewarder] authority.
+    pub auth: MutableRewarderWithAuthority<'info>,
+
+    /// [Quarry] to close.
+    #[account(mut, close = rent_recipient)]
+    pub quarry: Account<'info, Quarry>,
+
+    /// Receives the rent of the [Quarry].
+    /// CHECK: Arbitrary account.
+    #[account(mut)]
+    pub rent_recipient: UncheckedAccount<'info>,
+}
+
+impl<'info> Validate<'info> for CloseQuarry<'info> {
+    fn validate(&self) -> Result<()> {
+        self.auth.rewarder.assert_not_paused()?;
+        self.auth.validate()?;
+        assert_keys_eq!(self.quarry.rewarder, self.auth.rewarder);
+        assert_keys_neq!(self.rent_recipient, self.quarry);
+
+        let quarry = &self.quarry;
+        invariant!(
+            quarry.num_miners == quarry.num_closed_miners
+                && quarry.total_tokens_deposited == 0
+                && quarry.rewards_share == 0
+                && !quarry.has_pending_rewards_share,
+            QuarryNotEmpty
+        );
+        Ok(())
+    }
+}
+
+/// Emitted when a [Quarry] is closed.
+#[event]
+pub struct QuarryCloseEvent {
+    /// [Rewarder] of the [Quarry].
+    #[index]
+    pub rewarder: Pubkey,
+    /// [Mint] of the [Quarry] token.
+    pub token_mint: Pubkey,
+    /// When the event took place.
+    pub timestamp: i64,
+}
@@ -0,0 +1,37 @@
+//! Implementation of the [crate::quarry_mine::complete_unbond] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::complete_unbond] instruction.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, UserStake<'info>>) -> Result<()> {
+    ctx.accounts
+        .quarry
+        .assert_not_paused(&ctx.accounts.rewarder, PAUSE_SCOPE_WITHDRAW)?;
+    let current_ts = Clock::get()?.unix_timestamp;
+    let miner = &mut ctx.accounts.miner;
+    let amount = miner.unbonding_balance;
+    let penalty = miner.unbonding_penalty;
+    invariant!(amount != 0, NoUnbondingBalance);
+    invariant!(current_ts >= miner.unbonding_end_ts, UnbondingNotComplete);
+    invariant!(
+        amount <= ctx.accounts.miner_vault.amount,
+        InsufficientBalance
+    );
+    miner.unbonding_balance = 0;
+    miner.unbonding_penalty = 0;
+
+    let penalty =
+        ctx.accounts
+            .pay_early_exit_penalty(ctx.remaining_accounts, penalty, current_ts)?;
+    let amount = unwrap_int!(amount.checked_sub(penalty));
+    ctx.accounts.transfer_from_vault(amount)?;
+
+    emit!(WithdrawEvent {
+        timestamp: current_ts,
+        authority: ctx.accounts.authority.key(),
+        amount,
+        token: ctx.accounts.token_account.mint,
+    });
+
+    Ok(())
+}
@@ -3,7 +3,8 @@ use crate::*;
 /// Creates a [Miner] for the given authority.
 ///
 /// Anyone can call this; this is an associated account.
-pub fn handler(ctx: Context<CreateMiner>) -> Result<()> {
+/// If the [Quarry] has an allowlist, the `proof` must show that the authority is in it.
+pub fn handler(ctx: Context<CreateMiner>, proof: &[[u8; 32]]) -> Result<()> {
     let quarry = &mut ctx.accounts.quarry;
     let index = quarry.num_miners;
     quarry.num_miners = unwrap_int!(quarry.num_miners.checked_add(1));
@@ -17,6 +18,7 @@ pub fn handler(ctx: Context<CreateMiner>) -> Result<()> {
     miner.rewards_per_token_paid = 0;
     miner.balance = 0;
     miner.index = index;
+    ctx.accounts.quarry.approve_miner(miner, proof)?;
 
     emit!(MinerCreateEvent {
         authority: miner.authority,
@@ -73,7 +75,8 @@ pub struct CreateMiner<'info> {
 
 impl<'info> Validate<'info> for CreateMiner<'info> {
     fn validate(&self) -> Result<()> {
-        invariant!(!self.rewarder.is_paused, Paused);
+        self.quarry
+            .assert_not_paused(&self.rewarder, PAUSE_SCOPE_STAKE)?;
         assert_keys_eq!(self.miner_vault.owner, self.miner);
         assert_keys_eq!(self.miner_vault.mint, self.token_mint);
         invariant!(self.miner_vault.delegate.is_none());
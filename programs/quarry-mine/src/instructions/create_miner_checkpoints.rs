@@ -0,0 +1,78 @@
+//! Implementation of the [crate::quarry_mine::create_miner_checkpoints] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::create_miner_checkpoints] instruction.
+pub fn handler(ctx: Context<CreateMinerCheckpoints>) -> Result<()> {
+    let current_ts = Clock::get()?.unix_timestamp;
+
+    let checkpoints = &mut ctx.accounts.checkpoints;
+    checkpoints.miner = ctx.accounts.miner.key();
+    checkpoints.bump = unwrap_bump!(ctx, "checkpoints");
+    checkpoints.record(current_ts, ctx.accounts.miner.balance)?;
+
+    let miner = &mut ctx.accounts.miner;
+    miner.checkpoints = checkpoints.key();
+
+    emit!(MinerCheckpointsCreateEvent {
+        authority: miner.authority,
+        miner: miner.key(),
+        checkpoints: miner.checkpoints,
+        timestamp: current_ts,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::create_miner_checkpoints].
+#[derive(Accounts)]
+pub struct CreateMinerCheckpoints<'info> {
+    /// [Miner::authority].
+    pub authority: Signer<'info>,
+
+    /// [Miner] to record the balance of.
+    #[account(mut)]
+    pub miner: Account<'info, Miner>,
+
+    /// [MinerCheckpoints] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"MinerCheckpoints".as_ref(),
+            miner.key().to_bytes().as_ref()
+        ],
+        bump,
+        payer = payer,
+        space = 8 + MinerCheckpoints::LEN
+    )]
+    pub checkpoints: Box<Account<'info, MinerCheckpoints>>,
+
+    /// Payer of [MinerCheckpoints] creation.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Validate<'info> for CreateMinerCheckpoints<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.authority, self.miner.authority);
+        Ok(())
+    }
+}
+
+/// Emitted when [MinerCheckpoints] are created.
+#[event]
+pub struct MinerCheckpointsCreateEvent {
+    /// Authority of the [Miner].
+    #[index]
+    pub authority: Pubkey,
+    /// The [Miner].
+    #[index]
+    pub miner: Pubkey,
+    /// The [MinerCheckpoints].
+    pub checkpoints: Pubkey,
+    /// When the [MinerCheckpoints] were created.
+    pub timestamp: i64,
+}
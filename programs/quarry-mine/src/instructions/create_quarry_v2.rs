@@ -15,6 +15,8 @@ pub fn handler(ctx: Context<CreateQuarryV2>) -> Result<()> {
     quarry.rewarder = rewarder.key();
     quarry.annual_rewards_rate = 0;
     quarry.rewards_share = 0;
+    quarry.rewards_shares_generation = rewarder.rewards_shares_generation;
+    quarry.rewards_epoch = rewarder.rewards_epoch;
     quarry.token_mint_decimals = ctx.accounts.token_mint.decimals;
     quarry.token_mint_key = ctx.accounts.token_mint.key();
 
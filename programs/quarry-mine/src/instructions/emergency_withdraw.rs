@@ -0,0 +1,56 @@
+//! Implementation of the [crate::quarry_mine::emergency_withdraw] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::emergency_withdraw] instruction.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, UserStake<'info>>) -> Result<()> {
+    invariant!(
+        ctx.accounts
+            .quarry
+            .is_paused(&ctx.accounts.rewarder, PAUSE_SCOPE_WITHDRAW),
+        WithdrawalsNotPaused
+    );
+
+    let current_ts = Clock::get()?.unix_timestamp;
+    let rewards_forfeited = ctx.accounts.quarry.process_emergency_withdraw_internal(
+        current_ts,
+        &ctx.accounts.rewarder,
+        &mut ctx.accounts.miner,
+    )?;
+    checkpoints::record_balance_checkpoint(
+        &ctx.accounts.miner,
+        ctx.remaining_accounts,
+        current_ts,
+    )?;
+
+    // Return the entire vault, including any unbonding balance
+    let amount = ctx.accounts.miner_vault.amount;
+    ctx.accounts.transfer_from_vault(amount)?;
+
+    emit!(EmergencyWithdrawEvent {
+        authority: ctx.accounts.authority.key(),
+        quarry: ctx.accounts.quarry.key(),
+        amount,
+        rewards_forfeited,
+        timestamp: current_ts,
+    });
+
+    Ok(())
+}
+
+/// Emitted when a [Miner] makes an emergency withdrawal.
+#[event]
+pub struct EmergencyWithdrawEvent {
+    /// Authority of the [Miner].
+    #[index]
+    pub authority: Pubkey,
+    /// [Quarry] of the [Miner].
+    #[index]
+    pub quarry: Pubkey,
+    /// Amount of staked tokens returned.
+    pub amount: u64,
+    /// Unclaimed rewards given up by the [Miner].
+    pub rewards_forfeited: u64,
+    /// When the withdrawal was made.
+    pub timestamp: i64,
+}
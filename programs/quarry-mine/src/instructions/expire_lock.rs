@@ -0,0 +1,44 @@
+//! Implementation of the [crate::quarry_mine::expire_lock] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::expire_lock] instruction.
+pub fn handler(ctx: Context<ExpireLock>) -> Result<()> {
+    let current_ts = Clock::get()?.unix_timestamp;
+    let miner = &mut ctx.accounts.miner;
+    invariant!(
+        miner.lock_boost_bps != 0 && !miner.is_locked(current_ts),
+        LockNotExpired
+    );
+
+    // Settling the miner removes the boost of its expired lock.
+    ctx.accounts
+        .quarry
+        .update_rewards_and_miner(miner, &ctx.accounts.rewarder, current_ts)?;
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::expire_lock].
+#[derive(Accounts)]
+pub struct ExpireLock<'info> {
+    /// Miner whose lock has ended.
+    #[account(mut)]
+    pub miner: Account<'info, Miner>,
+
+    /// Quarry of the [Miner].
+    #[account(mut)]
+    pub quarry: Account<'info, Quarry>,
+
+    /// Rewarder
+    pub rewarder: Account<'info, Rewarder>,
+}
+
+impl<'info> Validate<'info> for ExpireLock<'info> {
+    fn validate(&self) -> Result<()> {
+        self.rewarder.assert_not_paused()?;
+        assert_keys_eq!(self.miner.quarry, self.quarry);
+        assert_keys_eq!(self.quarry.rewarder, self.rewarder);
+        Ok(())
+    }
+}
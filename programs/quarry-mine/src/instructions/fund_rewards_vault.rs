@@ -0,0 +1,80 @@
+//! Implementation of the [crate::quarry_mine::fund_rewards_vault] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::fund_rewards_vault] instruction.
+pub fn handler(ctx: Context<FundRewardsVault>, amount: u64) -> Result<()> {
+    let current_ts = Clock::get()?.unix_timestamp;
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.funder_token_account.to_account_info(),
+                to: ctx.accounts.rewards_vault.to_account_info(),
+                authority: ctx.accounts.funder.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let rewarder = &mut ctx.accounts.rewarder;
+    rewarder.fund_rewards_vault(amount, current_ts)?;
+
+    emit!(RewardsVaultFundEvent {
+        rewarder: rewarder.key(),
+        funder: ctx.accounts.funder.key(),
+        amount,
+        funded_until_ts: rewarder.funded_until_ts,
+        timestamp: current_ts,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::fund_rewards_vault].
+#[derive(Accounts)]
+pub struct FundRewardsVault<'info> {
+    /// Vault-funded [Rewarder].
+    #[account(mut)]
+    pub rewarder: Account<'info, Rewarder>,
+
+    /// The [Rewarder::rewards_vault].
+    #[account(mut)]
+    pub rewards_vault: Account<'info, TokenAccount>,
+
+    /// Owner of the [FundRewardsVault::funder_token_account].
+    pub funder: Signer<'info>,
+
+    /// Account the rewards are transferred from.
+    #[account(mut)]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    /// Token program.
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Validate<'info> for FundRewardsVault<'info> {
+    fn validate(&self) -> Result<()> {
+        self.rewarder.assert_not_paused()?;
+        invariant!(self.rewarder.is_vault_funded());
+        assert_keys_eq!(self.rewards_vault, self.rewarder.rewards_vault);
+        Ok(())
+    }
+}
+
+/// Emitted when the rewards vault of a [Rewarder] is funded.
+#[event]
+pub struct RewardsVaultFundEvent {
+    /// The [Rewarder].
+    #[index]
+    pub rewarder: Pubkey,
+    /// Who funded the vault.
+    pub funder: Pubkey,
+    /// Amount of rewards transferred to the vault.
+    pub amount: u64,
+    /// The new [Rewarder::funded_until_ts].
+    pub funded_until_ts: i64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
@@ -0,0 +1,33 @@
+//! Implementation of the [crate::quarry_mine::get_balance_at] instruction.
+
+use crate::*;
+use anchor_lang::solana_program::program::set_return_data;
+
+/// Handler for the [crate::quarry_mine::get_balance_at] instruction.
+pub fn handler(ctx: Context<GetBalanceAt>, timestamp: i64) -> Result<()> {
+    let current_ts = Clock::get()?.unix_timestamp;
+    invariant!(timestamp <= current_ts, InvalidTimestamp);
+    let balance = unwrap_opt!(
+        ctx.accounts.checkpoints.balance_at(timestamp),
+        BalanceCheckpointNotFound
+    );
+    set_return_data(&balance.try_to_vec()?);
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::get_balance_at].
+#[derive(Accounts)]
+pub struct GetBalanceAt<'info> {
+    /// The [Miner].
+    pub miner: Account<'info, Miner>,
+    /// The [Miner::checkpoints].
+    pub checkpoints: Box<Account<'info, MinerCheckpoints>>,
+}
+
+impl<'info> Validate<'info> for GetBalanceAt<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.miner.checkpoints, self.checkpoints);
+        assert_keys_eq!(self.checkpoints.miner, self.miner);
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+//! Implementation of the [crate::quarry_mine::get_claimable_rewards] instruction.
+
+use crate::*;
+use anchor_lang::solana_program::program::set_return_data;
+
+/// Handler for the [crate::quarry_mine::get_claimable_rewards] instruction.
+pub fn handler(ctx: Context<GetClaimableRewards>) -> Result<()> {
+    let current_ts = Clock::get()?.unix_timestamp;
+    let claimable = ClaimableRewards::compute(
+        *ctx.accounts.quarry,
+        *ctx.accounts.miner,
+        &ctx.accounts.rewarder,
+        current_ts,
+    )?;
+    set_return_data(&claimable.try_to_vec()?);
+    Ok(())
+}
+
+/// Rewards claimable by a [Miner], returned by [quarry_mine::get_claimable_rewards].
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClaimableRewards {
+    /// Rewards which would be received, net of fees.
+    pub amount: u64,
+    /// Fee which would be charged on the claim.
+    pub fees: u64,
+    /// Rewards currently issued to the [Miner] per second, rounded down.
+    pub rewards_per_second: u64,
+    /// When the rewards were computed.
+    pub timestamp: i64,
+}
+
+impl ClaimableRewards {
+    /// Computes the rewards claimable by a [Miner] without modifying any account.
+    pub fn compute(
+        mut quarry: Quarry,
+        mut miner: Miner,
+        rewarder: &Rewarder,
+        current_ts: i64,
+    ) -> Result<Self> {
+        quarry.update_rewards_and_miner(&mut miner, rewarder, current_ts)?;
+        let fees = rewarder.compute_claim_fee(miner.rewards_earned)?;
+        let rewards_per_second = quarry
+            .payroll(rewarder)
+            .calculate_rewards_per_second(current_ts, miner.weighted_balance())?;
+        Ok(Self {
+            amount: unwrap_int!(miner.rewards_earned.checked_sub(fees)),
+            fees,
+            rewards_per_second,
+            timestamp: current_ts,
+        })
+    }
+}
+
+/// Accounts for [quarry_mine::get_claimable_rewards].
+#[derive(Accounts)]
+pub struct GetClaimableRewards<'info> {
+    /// [Miner] to compute the rewards of.
+    pub miner: Account<'info, Miner>,
+
+    /// [Quarry] of the [Miner].
+    pub quarry: Account<'info, Quarry>,
+
+    /// [Rewarder] of the [Quarry].
+    pub rewarder: Account<'info, Rewarder>,
+}
+
+impl<'info> Validate<'info> for GetClaimableRewards<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.miner.quarry, self.quarry);
+        assert_keys_eq!(self.quarry.rewarder, self.rewarder);
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+//! Implementation of the [crate::quarry_mine::lock_tokens] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::lock_tokens] instruction.
+pub fn handler(ctx: Context<LockTokens>, lock_duration: i64) -> Result<()> {
+    let current_ts = Clock::get()?.unix_timestamp;
+    let miner = &mut ctx.accounts.miner;
+    ctx.accounts.quarry.process_lock_internal(
+        current_ts,
+        &ctx.accounts.rewarder,
+        miner,
+        lock_duration,
+    )?;
+
+    emit!(LockEvent {
+        authority: ctx.accounts.authority.key(),
+        quarry: miner.quarry,
+        lock_end_ts: miner.lock_end_ts,
+        lock_boost_bps: miner.lock_boost_bps,
+        timestamp: current_ts,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::lock_tokens].
+#[derive(Accounts)]
+pub struct LockTokens<'info> {
+    /// Miner authority (i.e. the user).
+    pub authority: Signer<'info>,
+
+    /// Miner.
+    #[account(mut)]
+    pub miner: Account<'info, Miner>,
+
+    /// Quarry of the [Miner].
+    #[account(mut)]
+    pub quarry: Account<'info, Quarry>,
+
+    /// Rewarder
+    pub rewarder: Account<'info, Rewarder>,
+}
+
+impl<'info> Validate<'info> for LockTokens<'info> {
+    fn validate(&self) -> Result<()> {
+        self.quarry
+            .assert_not_paused(&self.rewarder, PAUSE_SCOPE_STAKE)?;
+        invariant!(self.authority.is_signer, Unauthorized);
+        assert_keys_eq!(self.authority, self.miner.authority);
+        assert_keys_eq!(self.miner.quarry, self.quarry);
+        assert_keys_eq!(self.quarry.rewarder, self.rewarder);
+        Ok(())
+    }
+}
+
+/// Emitted when a [Miner] locks its balance.
+#[event]
+pub struct LockEvent {
+    /// Authority of the [Miner].
+    #[index]
+    pub authority: Pubkey,
+    /// [Quarry] of the [Miner].
+    #[index]
+    pub quarry: Pubkey,
+    /// When the lock ends.
+    pub lock_end_ts: i64,
+    /// Boost of the lock, in BPS.
+    pub lock_boost_bps: u64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
@@ -0,0 +1,94 @@
+//! Implementation of the [crate::quarry_mine::migrate_account] instruction.
+
+use crate::*;
+use anchor_lang::{system_program, Discriminator};
+
+/// Handler for the [crate::quarry_mine::migrate_account] instruction.
+pub fn handler(ctx: Context<MigrateAccount>) -> Result<()> {
+    let account = ctx.accounts.account.to_account_info();
+    let current_len = account.data_len();
+    let target_len = {
+        let data = account.try_borrow_data()?;
+        invariant!(
+            data.len() >= 8,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorNotFound
+        );
+        let discriminator = &data[..8];
+        if discriminator == Rewarder::discriminator() {
+            8 + Rewarder::LEN
+        } else if discriminator == Quarry::discriminator() {
+            8 + Quarry::LEN
+        } else if discriminator == Miner::discriminator() {
+            8 + Miner::LEN
+        } else {
+            return Err(error!(
+                anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+            ));
+        }
+    };
+    if current_len >= target_len {
+        // already up to date
+        return Ok(());
+    }
+
+    // Top up the account to remain rent exempt at its new size.
+    let rent_exempt_lamports = Rent::get()?.minimum_balance(target_len);
+    let lamports_needed = rent_exempt_lamports.saturating_sub(account.lamports());
+    if lamports_needed > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            lamports_needed,
+        )?;
+    }
+
+    // New fields are zero-initialized, which is their legacy behavior.
+    account.realloc(target_len, true)?;
+
+    emit!(AccountMigrateEvent {
+        account: account.key(),
+        previous_len: current_len as u64,
+        new_len: target_len as u64,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::migrate_account].
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// The [Rewarder], [Quarry] or [Miner] to migrate.
+    /// CHECK: Owner and discriminator are checked in the handler.
+    #[account(mut)]
+    pub account: UncheckedAccount<'info>,
+
+    /// Payer of the additional rent.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// System program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Validate<'info> for MigrateAccount<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(*self.account.owner, crate::ID);
+        Ok(())
+    }
+}
+
+/// Emitted when an account is migrated to the latest layout.
+#[event]
+pub struct AccountMigrateEvent {
+    /// The migrated account.
+    pub account: Pubkey,
+    /// Length of the account data before the migration.
+    pub previous_len: u64,
+    /// Length of the account data after the migration.
+    pub new_len: u64,
+}
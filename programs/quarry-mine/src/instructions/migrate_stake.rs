@@ -0,0 +1,173 @@
+//! Implementation of the [crate::quarry_mine::migrate_stake] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::migrate_stake] instruction.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, MigrateStake<'info>>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        // noop
+        return Ok(());
+    }
+    invariant!(
+        ctx.accounts.from_quarry.unbonding_period == 0,
+        UnbondingRequired
+    );
+    invariant!(
+        amount <= ctx.accounts.from_miner_vault.amount,
+        InsufficientBalance
+    );
+
+    let current_ts = Clock::get()?.unix_timestamp;
+    let from_quarry = &mut ctx.accounts.from_quarry;
+    invariant!(
+        from_quarry.compute_early_exit_penalty(&ctx.accounts.from_miner, current_ts, amount)? == 0,
+        EarlyExitPenaltyApplies
+    );
+    from_quarry.process_stake_action_internal(
+        StakeAction::Withdraw,
+        current_ts,
+        &ctx.accounts.from_rewarder,
+        &mut ctx.accounts.from_miner,
+        amount,
+    )?;
+
+    // Move the tokens between the vaults, signing as the source [Miner]
+    let from = &ctx.accounts.from_miner;
+    let miner_seeds = &[
+        b"Miner".as_ref(),
+        from.quarry.as_ref(),
+        from.authority.as_ref(),
+        &[from.bump],
+    ];
+    let signer_seeds = &[&miner_seeds[..]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.from_miner_vault.to_account_info(),
+                to: ctx.accounts.to_miner_vault.to_account_info(),
+                authority: from.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+
+    // the tokens keep the time they were staked at
+    let stake_ts = ctx.accounts.from_miner.last_stake_ts;
+    ctx.accounts.to_quarry.process_stake_action_internal(
+        StakeAction::Migrate { stake_ts },
+        current_ts,
+        &ctx.accounts.to_rewarder,
+        &mut ctx.accounts.to_miner,
+        amount,
+    )?;
+
+    checkpoints::record_balance_checkpoint(
+        &ctx.accounts.from_miner,
+        ctx.remaining_accounts,
+        current_ts,
+    )?;
+    checkpoints::record_balance_checkpoint(
+        &ctx.accounts.to_miner,
+        ctx.remaining_accounts,
+        current_ts,
+    )?;
+
+    emit!(StakeMigrateEvent {
+        authority: ctx.accounts.authority.key(),
+        from_quarry: ctx.accounts.from_quarry.key(),
+        to_quarry: ctx.accounts.to_quarry.key(),
+        amount,
+        timestamp: current_ts,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::migrate_stake].
+#[derive(Accounts)]
+pub struct MigrateStake<'info> {
+    /// Authority of both [Miner]s.
+    pub authority: Signer<'info>,
+
+    /// [Miner] to move the tokens from.
+    #[account(mut)]
+    pub from_miner: Box<Account<'info, Miner>>,
+
+    /// Vault of the [Miner] to move the tokens from.
+    #[account(mut)]
+    pub from_miner_vault: Box<Account<'info, TokenAccount>>,
+
+    /// [Quarry] to move the tokens from.
+    #[account(mut)]
+    pub from_quarry: Box<Account<'info, Quarry>>,
+
+    /// [Rewarder] of the [Quarry] to move the tokens from.
+    pub from_rewarder: Box<Account<'info, Rewarder>>,
+
+    /// [Miner] to move the tokens to.
+    #[account(mut)]
+    pub to_miner: Box<Account<'info, Miner>>,
+
+    /// Vault of the [Miner] to move the tokens to.
+    #[account(mut)]
+    pub to_miner_vault: Box<Account<'info, TokenAccount>>,
+
+    /// [Quarry] to move the tokens to.
+    #[account(mut)]
+    pub to_quarry: Box<Account<'info, Quarry>>,
+
+    /// [Rewarder] of the [Quarry] to move the tokens to.
+    pub to_rewarder: Box<Account<'info, Rewarder>>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Validate<'info> for MigrateStake<'info> {
+    fn validate(&self) -> Result<()> {
+        self.from_quarry
+            .assert_not_paused(&self.from_rewarder, PAUSE_SCOPE_WITHDRAW)?;
+        self.to_quarry
+            .assert_not_paused(&self.to_rewarder, PAUSE_SCOPE_STAKE)?;
+        assert_keys_neq!(self.from_quarry, self.to_quarry);
+        assert_keys_eq!(
+            self.from_quarry.token_mint_key,
+            self.to_quarry.token_mint_key
+        );
+
+        assert_keys_eq!(self.authority, self.from_miner.authority);
+        assert_keys_eq!(self.from_miner.quarry, self.from_quarry);
+        assert_keys_eq!(self.from_miner.token_vault_key, self.from_miner_vault);
+        assert_keys_eq!(self.from_quarry.rewarder, self.from_rewarder);
+
+        assert_keys_eq!(self.authority, self.to_miner.authority);
+        assert_keys_eq!(self.to_miner.quarry, self.to_quarry);
+        assert_keys_eq!(self.to_miner.token_vault_key, self.to_miner_vault);
+        assert_keys_eq!(self.to_quarry.rewarder, self.to_rewarder);
+
+        Ok(())
+    }
+}
+
+/// Emitted when staked tokens are moved from one [Quarry] to another.
+#[event]
+pub struct StakeMigrateEvent {
+    /// Authority of both [Miner]s.
+    #[index]
+    pub authority: Pubkey,
+    /// [Quarry] the tokens were moved from.
+    #[index]
+    pub from_quarry: Pubkey,
+    /// [Quarry] the tokens were moved to.
+    #[index]
+    pub to_quarry: Pubkey,
+    /// Amount of tokens staked into the destination [Quarry].
+    pub amount: u64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
@@ -1,17 +1,95 @@
+//! Instruction handlers.
+#![allow(ambiguous_glob_reexports)]
+
+pub mod add_reward_stream;
+pub mod approve_miner;
+pub mod claim_and_restake;
+pub mod claim_early_exit_penalties;
+pub mod claim_reward_streams;
 pub mod claim_rewards;
+pub mod claim_rewards_from_vault;
+pub mod claim_rewards_from_vault_with_referrer;
 pub mod claim_rewards_v2;
+pub mod claim_rewards_with_referrer;
+pub mod close_miner;
+pub mod close_quarry;
+pub mod complete_unbond;
 pub mod create_miner;
+pub mod create_miner_checkpoints;
 pub mod create_quarry;
 pub mod create_quarry_v2;
+pub mod emergency_withdraw;
+pub mod expire_lock;
+pub mod fund_rewards_vault;
+pub mod get_balance_at;
+pub mod get_claimable_rewards;
+pub mod lock_tokens;
+pub mod migrate_account;
+pub mod migrate_stake;
 pub mod new_rewarder;
 pub mod new_rewarder_v2;
+pub mod new_vault_funded_rewarder;
+pub mod request_unbond;
 pub mod rescue_tokens;
+pub mod set_allowlist_root;
+pub mod set_claim_fee;
+pub mod set_deposit_caps;
+pub mod set_early_exit_penalty;
+pub mod set_lock_params;
+pub mod set_max_claim_fee;
+pub mod set_paused_scopes;
+pub mod set_referral_fee;
+pub mod set_reward_stream;
+pub mod set_start_ts;
+pub mod set_unbonding_period;
+pub mod stake_tokens_for;
+pub mod sweep_rewards_vault;
+pub mod transfer_miner;
+pub mod update_quarry_rewards_batch;
+pub mod withdraw_tokens;
 
+pub use add_reward_stream::*;
+pub use approve_miner::*;
+pub use claim_and_restake::*;
+pub use claim_early_exit_penalties::*;
+pub use claim_reward_streams::*;
 pub use claim_rewards::*;
+pub use claim_rewards_from_vault::*;
+pub use claim_rewards_from_vault_with_referrer::*;
 pub use claim_rewards_v2::*;
+pub use claim_rewards_with_referrer::*;
+pub use close_miner::*;
+pub use close_quarry::*;
 pub use create_miner::*;
+pub use create_miner_checkpoints::*;
 pub use create_quarry::*;
 pub use create_quarry_v2::*;
+pub use emergency_withdraw::*;
+pub use expire_lock::*;
+pub use fund_rewards_vault::*;
+pub use get_balance_at::*;
+pub use get_claimable_rewards::*;
+pub use lock_tokens::*;
+pub use migrate_account::*;
+pub use migrate_stake::*;
 pub use new_rewarder::*;
 pub use new_rewarder_v2::*;
+pub use new_vault_funded_rewarder::*;
+pub use request_unbond::*;
 pub use rescue_tokens::*;
+pub use set_allowlist_root::*;
+pub use set_claim_fee::*;
+pub use set_deposit_caps::*;
+pub use set_early_exit_penalty::*;
+pub use set_lock_params::*;
+pub use set_max_claim_fee::*;
+pub use set_paused_scopes::*;
+pub use set_referral_fee::*;
+pub use set_reward_stream::*;
+pub use set_start_ts::*;
+pub use set_unbonding_period::*;
+pub use stake_tokens_for::*;
+pub use sweep_rewards_vault::*;
+pub use transfer_miner::*;
+pub use update_quarry_rewards_batch::*;
+pub use withdraw_tokens::*;
@@ -0,0 +1,81 @@
+//! Implementation of the [crate::quarry_mine::request_unbond] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::request_unbond] instruction.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, RequestUnbond<'info>>,
+    amount: u64,
+) -> Result<()> {
+    invariant!(amount != 0, InsufficientBalance);
+
+    let current_ts = Clock::get()?.unix_timestamp;
+    let miner = &mut ctx.accounts.miner;
+    ctx.accounts.quarry.process_unbond_request_internal(
+        current_ts,
+        &ctx.accounts.rewarder,
+        miner,
+        amount,
+    )?;
+    checkpoints::record_balance_checkpoint(miner, ctx.remaining_accounts, current_ts)?;
+
+    emit!(UnbondRequestEvent {
+        authority: ctx.accounts.authority.key(),
+        quarry: miner.quarry,
+        amount,
+        unbonding_balance: miner.unbonding_balance,
+        unbonding_end_ts: miner.unbonding_end_ts,
+        timestamp: current_ts,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::request_unbond].
+#[derive(Accounts)]
+pub struct RequestUnbond<'info> {
+    /// Miner authority (i.e. the user).
+    pub authority: Signer<'info>,
+
+    /// Miner.
+    #[account(mut)]
+    pub miner: Account<'info, Miner>,
+
+    /// Quarry of the [Miner].
+    #[account(mut)]
+    pub quarry: Account<'info, Quarry>,
+
+    /// Rewarder
+    pub rewarder: Account<'info, Rewarder>,
+}
+
+impl<'info> Validate<'info> for RequestUnbond<'info> {
+    fn validate(&self) -> Result<()> {
+        self.quarry
+            .assert_not_paused(&self.rewarder, PAUSE_SCOPE_WITHDRAW)?;
+        invariant!(self.authority.is_signer, Unauthorized);
+        assert_keys_eq!(self.authority, self.miner.authority);
+        assert_keys_eq!(self.miner.quarry, self.quarry);
+        assert_keys_eq!(self.quarry.rewarder, self.rewarder);
+        Ok(())
+    }
+}
+
+/// Emitted when a [Miner] requests an unbond.
+#[event]
+pub struct UnbondRequestEvent {
+    /// Authority of the [Miner].
+    #[index]
+    pub authority: Pubkey,
+    /// [Quarry] of the [Miner].
+    #[index]
+    pub quarry: Pubkey,
+    /// Amount requested.
+    pub amount: u64,
+    /// Total unbonding balance of the [Miner].
+    pub unbonding_balance: u64,
+    /// When the unbonding balance may be withdrawn.
+    pub unbonding_end_ts: i64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
@@ -0,0 +1,52 @@
+//! Implementation of the [crate::quarry_mine::set_allowlist_root] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::set_allowlist_root] instruction.
+pub fn handler(ctx: Context<SetAllowlistRoot>, allowlist_root: [u8; 32]) -> Result<()> {
+    let quarry = &mut ctx.accounts.quarry;
+    let previous_allowlist_root = quarry.allowlist_root;
+    quarry.allowlist_root = allowlist_root;
+
+    emit!(QuarryAllowlistRootUpdateEvent {
+        token_mint: quarry.token_mint_key,
+        previous_allowlist_root,
+        allowlist_root,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::set_allowlist_root].
+#[derive(Accounts)]
+pub struct SetAllowlistRoot<'info> {
+    /// [Rewarder] of the [Quarry].
+    pub auth: ReadOnlyRewarderWithAuthority<'info>,
+
+    /// [Quarry] updated.
+    #[account(mut)]
+    pub quarry: Account<'info, Quarry>,
+}
+
+impl<'info> Validate<'info> for SetAllowlistRoot<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.quarry.rewarder, self.auth.rewarder);
+        self.auth.rewarder.assert_not_paused()?;
+        self.auth.validate()?;
+        Ok(())
+    }
+}
+
+/// Emitted when the allowlist of a [Quarry] is updated.
+#[event]
+pub struct QuarryAllowlistRootUpdateEvent {
+    /// [Mint] of the [Quarry] token.
+    pub token_mint: Pubkey,
+    /// Previous allowlist root.
+    pub previous_allowlist_root: [u8; 32],
+    /// New allowlist root. If zero, the [Quarry] is open to all.
+    pub allowlist_root: [u8; 32],
+    /// When the event took place.
+    pub timestamp: i64,
+}
@@ -0,0 +1,55 @@
+//! Implementation of the [crate::quarry_mine::set_claim_fee] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::set_claim_fee] instruction.
+pub fn handler(ctx: Context<SetClaimFee>, claim_fee_millibps: u64) -> Result<()> {
+    let rewarder = &mut ctx.accounts.rewarder;
+    let previous_claim_fee_millibps = rewarder.effective_claim_fee_millibps();
+    rewarder.set_claim_fee(claim_fee_millibps)?;
+
+    emit!(RewarderClaimFeeUpdateEvent {
+        rewarder: rewarder.key(),
+        previous_claim_fee_millibps,
+        claim_fee_millibps,
+        max_claim_fee_millibps: rewarder.max_claim_fee_millibps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::set_claim_fee].
+#[derive(Accounts)]
+pub struct SetClaimFee<'info> {
+    /// The [addresses::FEE_SETTER].
+    pub fee_setter: Signer<'info>,
+
+    /// [Rewarder] to update.
+    #[account(mut)]
+    pub rewarder: Account<'info, Rewarder>,
+}
+
+impl<'info> Validate<'info> for SetClaimFee<'info> {
+    fn validate(&self) -> Result<()> {
+        self.rewarder.assert_not_paused()?;
+        assert_keys_eq!(self.fee_setter, addresses::FEE_SETTER, Unauthorized);
+        Ok(())
+    }
+}
+
+/// Emitted when the claim fee or maximum claim fee of a [Rewarder] is updated.
+#[event]
+pub struct RewarderClaimFeeUpdateEvent {
+    /// The [Rewarder].
+    #[index]
+    pub rewarder: Pubkey,
+    /// Claim fee charged before the update, in milliBPS.
+    pub previous_claim_fee_millibps: u64,
+    /// Claim fee charged after the update, in milliBPS.
+    pub claim_fee_millibps: u64,
+    /// Maximum claim fee after the update, in milliBPS.
+    pub max_claim_fee_millibps: u64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
@@ -0,0 +1,56 @@
+//! Implementation of the [crate::quarry_mine::set_deposit_caps] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::set_deposit_caps] instruction.
+pub fn handler(
+    ctx: Context<SetDepositCaps>,
+    max_total_tokens_deposited: u64,
+    max_miner_balance: u64,
+) -> Result<()> {
+    let quarry = &mut ctx.accounts.quarry;
+    quarry.max_total_tokens_deposited = max_total_tokens_deposited;
+    quarry.max_miner_balance = max_miner_balance;
+
+    emit!(QuarryDepositCapsUpdateEvent {
+        token_mint: quarry.token_mint_key,
+        max_total_tokens_deposited,
+        max_miner_balance,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::set_deposit_caps].
+#[derive(Accounts)]
+pub struct SetDepositCaps<'info> {
+    /// [Rewarder] of the [Quarry].
+    pub auth: ReadOnlyRewarderWithAuthority<'info>,
+
+    /// [Quarry] updated.
+    #[account(mut)]
+    pub quarry: Account<'info, Quarry>,
+}
+
+impl<'info> Validate<'info> for SetDepositCaps<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.quarry.rewarder, self.auth.rewarder);
+        self.auth.rewarder.assert_not_paused()?;
+        self.auth.validate()?;
+        Ok(())
+    }
+}
+
+/// Emitted when the deposit caps of a [Quarry] are updated.
+#[event]
+pub struct QuarryDepositCapsUpdateEvent {
+    /// [Mint] of the [Quarry] token.
+    pub token_mint: Pubkey,
+    /// New maximum of [Quarry::total_tokens_deposited]. If zero, there is no cap.
+    pub max_total_tokens_deposited: u64,
+    /// New maximum [Miner::balance]. If zero, there is no cap.
+    pub max_miner_balance: u64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
@@ -0,0 +1,91 @@
+//! Implementation of the [crate::quarry_mine::set_early_exit_penalty] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::set_early_exit_penalty] instruction.
+///
+/// Quarries which do not stake the rewards token and redistribute penalties must supply
+/// the [Quarry::early_exit_penalty_vault] as a remaining account.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetEarlyExitPenalty<'info>>,
+    early_exit_period: i64,
+    early_exit_penalty_bps: u64,
+    treasury: Pubkey,
+) -> Result<()> {
+    invariant!(early_exit_period >= 0, InvalidEarlyExitPenalty);
+    invariant!(early_exit_penalty_bps <= MAX_BPS, InvalidEarlyExitPenalty);
+
+    let rewarder = &ctx.accounts.auth.rewarder;
+    let quarry = &mut ctx.accounts.quarry;
+    quarry.early_exit_period = early_exit_period;
+    quarry.early_exit_penalty_bps = early_exit_penalty_bps;
+    quarry.early_exit_treasury = treasury;
+
+    // penalties in other tokens are held by the quarry until its miners claim them
+    if quarry.redistributes_early_exit_penalty_in_stake(rewarder) && early_exit_penalty_bps != 0 {
+        let penalty_vault: Account<TokenAccount> = Account::try_from(unwrap_opt!(
+            ctx.remaining_accounts.first(),
+            InvalidRemainingAccounts
+        ))?;
+        assert_keys_eq!(penalty_vault.owner, quarry.key(), InvalidEarlyExitPenalty);
+        assert_keys_eq!(
+            penalty_vault.mint,
+            quarry.token_mint_key,
+            InvalidEarlyExitPenalty
+        );
+        // miners may still have penalties to claim from the previous vault
+        if quarry.early_exit_penalty_vault != Pubkey::default() {
+            assert_keys_eq!(
+                penalty_vault,
+                quarry.early_exit_penalty_vault,
+                InvalidEarlyExitPenalty
+            );
+        }
+        quarry.early_exit_penalty_vault = penalty_vault.key();
+    }
+
+    emit!(QuarryEarlyExitPenaltyUpdateEvent {
+        token_mint: quarry.token_mint_key,
+        early_exit_period,
+        early_exit_penalty_bps,
+        treasury,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::set_early_exit_penalty].
+#[derive(Accounts)]
+pub struct SetEarlyExitPenalty<'info> {
+    /// [Rewarder] of the [Quarry].
+    pub auth: ReadOnlyRewarderWithAuthority<'info>,
+
+    /// [Quarry] updated.
+    #[account(mut)]
+    pub quarry: Account<'info, Quarry>,
+}
+
+impl<'info> Validate<'info> for SetEarlyExitPenalty<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.quarry.rewarder, self.auth.rewarder);
+        self.auth.rewarder.assert_not_paused()?;
+        self.auth.validate()?;
+        Ok(())
+    }
+}
+
+/// Emitted when the early exit penalty of a [Quarry] is updated.
+#[event]
+pub struct QuarryEarlyExitPenaltyUpdateEvent {
+    /// [Mint] of the [Quarry] token.
+    pub token_mint: Pubkey,
+    /// New early exit period.
+    pub early_exit_period: i64,
+    /// New early exit penalty.
+    pub early_exit_penalty_bps: u64,
+    /// New treasury. If default, penalties are redistributed.
+    pub treasury: Pubkey,
+    /// When the event took place.
+    pub timestamp: i64,
+}
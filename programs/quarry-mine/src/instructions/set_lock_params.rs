@@ -0,0 +1,62 @@
+//! Implementation of the [crate::quarry_mine::set_lock_params] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::set_lock_params] instruction.
+pub fn handler(
+    ctx: Context<SetLockParams>,
+    max_lock_duration: i64,
+    max_lock_boost_bps: u64,
+) -> Result<()> {
+    invariant!(max_lock_duration >= 0, InvalidLockDuration);
+    invariant!(
+        max_lock_boost_bps <= MAX_LOCK_BOOST_BPS,
+        MaxLockBoostExceeded
+    );
+
+    let quarry = &mut ctx.accounts.quarry;
+    quarry.max_lock_duration = max_lock_duration;
+    quarry.max_lock_boost_bps = max_lock_boost_bps;
+
+    emit!(QuarryLockParamsUpdateEvent {
+        token_mint: quarry.token_mint_key,
+        max_lock_duration,
+        max_lock_boost_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::set_lock_params].
+#[derive(Accounts)]
+pub struct SetLockParams<'info> {
+    /// [Rewarder] of the [Quarry].
+    pub auth: ReadOnlyRewarderWithAuthority<'info>,
+
+    /// [Quarry] updated.
+    #[account(mut)]
+    pub quarry: Account<'info, Quarry>,
+}
+
+impl<'info> Validate<'info> for SetLockParams<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.quarry.rewarder, self.auth.rewarder);
+        self.auth.rewarder.assert_not_paused()?;
+        self.auth.validate()?;
+        Ok(())
+    }
+}
+
+/// Emitted when the lock parameters of a [Quarry] are updated.
+#[event]
+pub struct QuarryLockParamsUpdateEvent {
+    /// [Mint] of the [Quarry] token.
+    pub token_mint: Pubkey,
+    /// New maximum lock duration.
+    pub max_lock_duration: i64,
+    /// New maximum lock boost.
+    pub max_lock_boost_bps: u64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
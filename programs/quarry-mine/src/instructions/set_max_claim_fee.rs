@@ -0,0 +1,35 @@
+//! Implementation of the [crate::quarry_mine::set_max_claim_fee] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::set_max_claim_fee] instruction.
+pub fn handler(ctx: Context<SetMaxClaimFee>, max_claim_fee_millibps: u64) -> Result<()> {
+    let rewarder = &mut ctx.accounts.auth.rewarder;
+    let previous_claim_fee_millibps = rewarder.effective_claim_fee_millibps();
+    rewarder.lower_max_claim_fee(max_claim_fee_millibps)?;
+
+    emit!(RewarderClaimFeeUpdateEvent {
+        rewarder: rewarder.key(),
+        previous_claim_fee_millibps,
+        claim_fee_millibps: rewarder.effective_claim_fee_millibps(),
+        max_claim_fee_millibps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::set_max_claim_fee].
+#[derive(Accounts)]
+pub struct SetMaxClaimFee<'info> {
+    /// [Rewarder] to update.
+    pub auth: MutableRewarderWithAuthority<'info>,
+}
+
+impl<'info> Validate<'info> for SetMaxClaimFee<'info> {
+    fn validate(&self) -> Result<()> {
+        self.auth.rewarder.assert_not_paused()?;
+        self.auth.validate()?;
+        Ok(())
+    }
+}
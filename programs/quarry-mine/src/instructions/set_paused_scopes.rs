@@ -0,0 +1,85 @@
+//! Implementation of the [crate::quarry_mine::set_paused_scopes]
+//! and [crate::quarry_mine::set_quarry_paused_scopes] instructions.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::set_paused_scopes] instruction.
+pub fn handler(ctx: Context<MutableRewarderWithPauseAuthority>, paused_scopes: u8) -> Result<()> {
+    invariant!(paused_scopes & !PAUSE_SCOPE_ALL == 0, InvalidPausedScopes);
+
+    let rewarder = &mut ctx.accounts.rewarder;
+    rewarder.paused_scopes = paused_scopes;
+
+    emit!(RewarderPausedScopesUpdateEvent {
+        rewarder: rewarder.key(),
+        paused_scopes,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Handler for the [crate::quarry_mine::set_quarry_paused_scopes] instruction.
+pub fn quarry_handler(ctx: Context<SetQuarryPausedScopes>, paused_scopes: u8) -> Result<()> {
+    invariant!(paused_scopes & !PAUSE_SCOPE_ALL == 0, InvalidPausedScopes);
+
+    let quarry = &mut ctx.accounts.quarry;
+    quarry.paused_scopes = paused_scopes;
+
+    emit!(QuarryPausedScopesUpdateEvent {
+        token_mint: quarry.token_mint_key,
+        paused_scopes,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::set_quarry_paused_scopes].
+#[derive(Accounts)]
+pub struct SetQuarryPausedScopes<'info> {
+    /// Pause authority of the [Rewarder].
+    pub pause_authority: Signer<'info>,
+
+    /// [Rewarder] of the [Quarry].
+    pub rewarder: Account<'info, Rewarder>,
+
+    /// [Quarry] updated.
+    #[account(mut)]
+    pub quarry: Account<'info, Quarry>,
+}
+
+impl<'info> Validate<'info> for SetQuarryPausedScopes<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(
+            self.rewarder.pause_authority,
+            self.pause_authority,
+            Unauthorized
+        );
+        assert_keys_eq!(self.quarry.rewarder, self.rewarder);
+        Ok(())
+    }
+}
+
+/// Emitted when the paused scopes of a [Rewarder] are updated.
+#[event]
+pub struct RewarderPausedScopesUpdateEvent {
+    /// The [Rewarder].
+    #[index]
+    pub rewarder: Pubkey,
+    /// New [Rewarder::paused_scopes].
+    pub paused_scopes: u8,
+    /// When the event took place.
+    pub timestamp: i64,
+}
+
+/// Emitted when the paused scopes of a [Quarry] are updated.
+#[event]
+pub struct QuarryPausedScopesUpdateEvent {
+    /// [Mint] of the [Quarry] token.
+    pub token_mint: Pubkey,
+    /// New [Quarry::paused_scopes].
+    pub paused_scopes: u8,
+    /// When the event took place.
+    pub timestamp: i64,
+}
@@ -0,0 +1,52 @@
+//! Implementation of the [crate::quarry_mine::set_referral_fee] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::set_referral_fee] instruction.
+pub fn handler(ctx: Context<SetReferralFee>, referral_fee_millibps: u64) -> Result<()> {
+    let rewarder = &mut ctx.accounts.rewarder;
+    let previous_referral_fee_millibps = rewarder.referral_fee_millibps;
+    rewarder.set_referral_fee(referral_fee_millibps)?;
+
+    emit!(RewarderReferralFeeUpdateEvent {
+        rewarder: rewarder.key(),
+        previous_referral_fee_millibps,
+        referral_fee_millibps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::set_referral_fee].
+#[derive(Accounts)]
+pub struct SetReferralFee<'info> {
+    /// The [addresses::FEE_SETTER].
+    pub fee_setter: Signer<'info>,
+
+    /// [Rewarder] to update.
+    #[account(mut)]
+    pub rewarder: Account<'info, Rewarder>,
+}
+
+impl<'info> Validate<'info> for SetReferralFee<'info> {
+    fn validate(&self) -> Result<()> {
+        self.rewarder.assert_not_paused()?;
+        assert_keys_eq!(self.fee_setter, addresses::FEE_SETTER, Unauthorized);
+        Ok(())
+    }
+}
+
+/// Emitted when the referral fee of a [Rewarder] is updated.
+#[event]
+pub struct RewarderReferralFeeUpdateEvent {
+    /// The [Rewarder].
+    #[index]
+    pub rewarder: Pubkey,
+    /// Previous referral fee, in milliBPS.
+    pub previous_referral_fee_millibps: u64,
+    /// New referral fee, in milliBPS.
+    pub referral_fee_millibps: u64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
@@ -0,0 +1,85 @@
+//! Implementation of the [crate::quarry_mine::set_reward_stream] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::set_reward_stream] instruction.
+pub fn handler(
+    ctx: Context<SetRewardStream>,
+    index: u8,
+    annual_rewards_rate: u64,
+    famine_ts: i64,
+) -> Result<()> {
+    invariant!(
+        annual_rewards_rate <= MAX_ANNUAL_REWARDS_RATE,
+        MaxAnnualRewardsRateExceeded
+    );
+
+    let quarry = &mut ctx.accounts.quarry;
+    let total_weighted_tokens = quarry.total_weighted_tokens();
+    let start_ts = quarry.start_ts;
+    let stream = unwrap_opt!(
+        quarry.reward_streams.get_mut(index as usize),
+        RewardStreamNotFound
+    );
+    invariant!(stream.is_active(), RewardStreamNotFound);
+
+    // Accrue at the previous rate before changing it.
+    let current_ts = Clock::get()?.unix_timestamp;
+    let payroll = stream
+        .payroll(total_weighted_tokens)
+        .with_start_ts(start_ts);
+    stream.update_rewards_internal(current_ts, &payroll)?;
+
+    let previous_rate = stream.annual_rewards_rate;
+    stream.annual_rewards_rate = annual_rewards_rate;
+    stream.famine_ts = famine_ts;
+
+    emit!(RewardStreamUpdateEvent {
+        quarry: quarry.key(),
+        index,
+        previous_rate,
+        new_rate: annual_rewards_rate,
+        famine_ts,
+        timestamp: current_ts,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::set_reward_stream].
+#[derive(Accounts)]
+pub struct SetRewardStream<'info> {
+    /// [Rewarder] of the [Quarry].
+    pub auth: ReadOnlyRewarderWithAuthority<'info>,
+
+    /// [Quarry] updated.
+    #[account(mut)]
+    pub quarry: Account<'info, Quarry>,
+}
+
+impl<'info> Validate<'info> for SetRewardStream<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.quarry.rewarder, self.auth.rewarder);
+        self.auth.rewarder.assert_not_paused()?;
+        self.auth.validate()?;
+        Ok(())
+    }
+}
+
+/// Emitted when the rate or famine of a [RewardStream] is updated.
+#[event]
+pub struct RewardStreamUpdateEvent {
+    /// The [Quarry].
+    #[index]
+    pub quarry: Pubkey,
+    /// Index of the stream in [Quarry::reward_streams].
+    pub index: u8,
+    /// Previous annual rewards rate.
+    pub previous_rate: u64,
+    /// New annual rewards rate.
+    pub new_rate: u64,
+    /// New famine timestamp.
+    pub famine_ts: i64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
@@ -0,0 +1,63 @@
+//! Implementation of the [crate::quarry_mine::set_start_ts] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::set_start_ts] instruction.
+pub fn handler(ctx: Context<SetStartTs>, start_ts: i64) -> Result<()> {
+    let current_ts = Clock::get()?.unix_timestamp;
+    invariant!(start_ts >= current_ts, InvalidStartTs);
+
+    // Accrue up to now, so that the new start only affects future rewards.
+    let rewarder = &mut ctx.accounts.auth.rewarder;
+    let quarry = &mut ctx.accounts.quarry;
+    let payroll = quarry.payroll(rewarder);
+    quarry.update_rewards_internal(current_ts, rewarder, &payroll)?;
+    quarry.sync_rewards_shares_generation(rewarder)?;
+    quarry.sync_rewards_epoch(rewarder)?;
+    quarry.report_rewards_totals(rewarder)?;
+
+    let previous_start_ts = quarry.start_ts;
+    quarry.start_ts = start_ts;
+
+    emit!(QuarryStartTsUpdateEvent {
+        token_mint: quarry.token_mint_key,
+        previous_start_ts,
+        start_ts,
+        timestamp: current_ts,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::set_start_ts].
+#[derive(Accounts)]
+pub struct SetStartTs<'info> {
+    /// [Rewarder] of the [Quarry].
+    pub auth: MutableRewarderWithAuthority<'info>,
+
+    /// [Quarry] updated.
+    #[account(mut)]
+    pub quarry: Account<'info, Quarry>,
+}
+
+impl<'info> Validate<'info> for SetStartTs<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.quarry.rewarder, self.auth.rewarder);
+        self.auth.rewarder.assert_not_paused()?;
+        self.auth.validate()?;
+        Ok(())
+    }
+}
+
+/// Emitted when the start of a [Quarry]'s rewards is updated.
+#[event]
+pub struct QuarryStartTsUpdateEvent {
+    /// [Mint] of the [Quarry] token.
+    pub token_mint: Pubkey,
+    /// Previous [Quarry::start_ts].
+    pub previous_start_ts: i64,
+    /// New [Quarry::start_ts].
+    pub start_ts: i64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
@@ -0,0 +1,50 @@
+//! Implementation of the [crate::quarry_mine::set_unbonding_period] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::set_unbonding_period] instruction.
+pub fn handler(ctx: Context<SetUnbondingPeriod>, unbonding_period: i64) -> Result<()> {
+    invariant!(unbonding_period >= 0, InvalidUnbondingPeriod);
+
+    let quarry = &mut ctx.accounts.quarry;
+    quarry.unbonding_period = unbonding_period;
+
+    emit!(QuarryUnbondingPeriodUpdateEvent {
+        token_mint: quarry.token_mint_key,
+        unbonding_period,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::set_unbonding_period].
+#[derive(Accounts)]
+pub struct SetUnbondingPeriod<'info> {
+    /// [Rewarder] of the [Quarry].
+    pub auth: ReadOnlyRewarderWithAuthority<'info>,
+
+    /// [Quarry] updated.
+    #[account(mut)]
+    pub quarry: Account<'info, Quarry>,
+}
+
+impl<'info> Validate<'info> for SetUnbondingPeriod<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.quarry.rewarder, self.auth.rewarder);
+        self.auth.rewarder.assert_not_paused()?;
+        self.auth.validate()?;
+        Ok(())
+    }
+}
+
+/// Emitted when the unbonding period of a [Quarry] is updated.
+#[event]
+pub struct QuarryUnbondingPeriodUpdateEvent {
+    /// [Mint] of the [Quarry] token.
+    pub token_mint: Pubkey,
+    /// New unbonding period.
+    pub unbonding_period: i64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
@@ -0,0 +1,122 @@
+//! Implementation of the [crate::quarry_mine::stake_tokens_for] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::stake_tokens_for] instruction.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, StakeTokensFor<'info>>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        // noop
+        return Ok(());
+    }
+
+    // Only the authority's own deposits restart its early exit period;
+    // deposits by others are penalized on their own
+    let action = if ctx.accounts.depositor.key() == ctx.accounts.miner.authority {
+        StakeAction::Stake
+    } else {
+        StakeAction::StakeFor
+    };
+    let current_ts = Clock::get()?.unix_timestamp;
+    ctx.accounts.quarry.process_stake_action_internal(
+        action,
+        current_ts,
+        &ctx.accounts.rewarder,
+        &mut ctx.accounts.miner,
+        amount,
+    )?;
+    checkpoints::record_balance_checkpoint(
+        &ctx.accounts.miner,
+        ctx.remaining_accounts,
+        current_ts,
+    )?;
+
+    // Transfer LP tokens from the depositor to the quarry vault
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.depositor_token_account.to_account_info(),
+                to: ctx.accounts.miner_vault.to_account_info(),
+                authority: ctx.accounts.depositor.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    emit!(StakeForEvent {
+        depositor: ctx.accounts.depositor.key(),
+        authority: ctx.accounts.miner.authority,
+        token: ctx.accounts.depositor_token_account.mint,
+        amount,
+        timestamp: current_ts,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::stake_tokens_for].
+#[derive(Accounts)]
+pub struct StakeTokensFor<'info> {
+    /// Owner of the tokens staked.
+    pub depositor: Signer<'info>,
+
+    /// [TokenAccount] the tokens are staked from.
+    #[account(mut)]
+    pub depositor_token_account: Account<'info, TokenAccount>,
+
+    /// [Miner] to stake into.
+    #[account(mut)]
+    pub miner: Account<'info, Miner>,
+
+    /// [Quarry] of the [Miner].
+    #[account(mut)]
+    pub quarry: Account<'info, Quarry>,
+
+    /// Vault of the [Miner].
+    #[account(mut)]
+    pub miner_vault: Account<'info, TokenAccount>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+
+    /// [Rewarder].
+    pub rewarder: Account<'info, Rewarder>,
+}
+
+impl<'info> Validate<'info> for StakeTokensFor<'info> {
+    fn validate(&self) -> Result<()> {
+        self.quarry
+            .assert_not_paused(&self.rewarder, PAUSE_SCOPE_STAKE)?;
+        assert_keys_eq!(self.miner.quarry, self.quarry);
+        assert_keys_eq!(self.quarry.rewarder, self.rewarder);
+
+        let staked_mint = self.quarry.token_mint_key;
+        assert_keys_eq!(self.miner.token_vault_key, self.miner_vault);
+        assert_keys_eq!(self.miner_vault.mint, staked_mint);
+        assert_keys_eq!(self.miner_vault.owner, self.miner);
+        assert_keys_eq!(self.depositor_token_account.mint, staked_mint);
+
+        Ok(())
+    }
+}
+
+/// Emitted when tokens are staked into a [Miner] on behalf of its authority.
+#[event]
+pub struct StakeForEvent {
+    /// Owner of the tokens staked.
+    #[index]
+    pub depositor: Pubkey,
+    /// Authority of the [Miner] staked into.
+    #[index]
+    pub authority: Pubkey,
+    /// Mint of token staked.
+    #[index]
+    pub token: Pubkey,
+    /// Amount staked.
+    pub amount: u64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
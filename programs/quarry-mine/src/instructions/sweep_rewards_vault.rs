@@ -0,0 +1,101 @@
+//! Implementation of the [crate::quarry_mine::sweep_rewards_vault] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::sweep_rewards_vault] instruction.
+///
+/// Rewards owed to [Miner]s stay in the vault.
+pub fn handler(ctx: Context<SweepRewardsVault>) -> Result<()> {
+    let current_ts = Clock::get()?.unix_timestamp;
+    let rewarder = &mut ctx.accounts.auth.rewarder;
+    invariant!(
+        current_ts >= rewarder.funded_until_ts,
+        RewardsVaultNotExhausted
+    );
+
+    // quarries report their final rewards as they synchronize with an epoch
+    // which started after the vault ran out
+    if rewarder.rewards_epoch_ts < rewarder.funded_until_ts {
+        rewarder.start_rewards_epoch(current_ts)?;
+    }
+    let rewards_outstanding = rewarder.rewards_outstanding()?;
+    let amount = if rewarder.has_final_rewards_totals() {
+        ctx.accounts
+            .rewards_vault
+            .amount
+            .saturating_sub(rewards_outstanding)
+    } else {
+        0
+    };
+    if amount > 0 {
+        let seeds = gen_rewarder_signer_seeds!(rewarder);
+        let signer_seeds = &[&seeds[..]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.rewards_vault.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: rewarder.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+    }
+
+    emit!(RewardsVaultSweepEvent {
+        rewarder: rewarder.key(),
+        destination: ctx.accounts.destination.key(),
+        amount,
+        rewards_outstanding,
+        timestamp: current_ts,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::sweep_rewards_vault].
+#[derive(Accounts)]
+pub struct SweepRewardsVault<'info> {
+    /// Vault-funded [Rewarder].
+    pub auth: MutableRewarderWithAuthority<'info>,
+
+    /// The [Rewarder::rewards_vault].
+    #[account(mut)]
+    pub rewards_vault: Account<'info, TokenAccount>,
+
+    /// Account to send the leftover rewards to.
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+
+    /// Token program.
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Validate<'info> for SweepRewardsVault<'info> {
+    fn validate(&self) -> Result<()> {
+        self.auth.rewarder.assert_not_paused()?;
+        self.auth.validate()?;
+        invariant!(self.auth.rewarder.is_vault_funded());
+        assert_keys_eq!(self.rewards_vault, self.auth.rewarder.rewards_vault);
+        assert_keys_neq!(self.destination, self.rewards_vault);
+        Ok(())
+    }
+}
+
+/// Emitted when the leftover rewards of a [Rewarder] are swept.
+#[event]
+pub struct RewardsVaultSweepEvent {
+    /// The [Rewarder].
+    #[index]
+    pub rewarder: Pubkey,
+    /// Account which received the leftover rewards.
+    pub destination: Pubkey,
+    /// Amount of rewards swept.
+    pub amount: u64,
+    /// Rewards kept in the vault for [Miner]s to claim.
+    pub rewards_outstanding: u64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
@@ -0,0 +1,127 @@
+//! Implementation of the [crate::quarry_mine::transfer_miner] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::transfer_miner] instruction.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, TransferMiner<'info>>) -> Result<()> {
+    let current_ts = Clock::get()?.unix_timestamp;
+    let from = &mut ctx.accounts.from_miner;
+    let to = &mut ctx.accounts.to_miner;
+    let balance = from.balance;
+    ctx.accounts.quarry.process_transfer_miner_internal(
+        current_ts,
+        &ctx.accounts.rewarder,
+        from,
+        to,
+    )?;
+    checkpoints::record_balance_checkpoint(from, ctx.remaining_accounts, current_ts)?;
+    checkpoints::record_balance_checkpoint(to, ctx.remaining_accounts, current_ts)?;
+
+    // Sign a transfer of the entire vault as the source [Miner]
+    let amount = ctx.accounts.from_miner_vault.amount;
+    if amount != 0 {
+        let from = &ctx.accounts.from_miner;
+        let miner_seeds = &[
+            b"Miner".as_ref(),
+            from.quarry.as_ref(),
+            from.authority.as_ref(),
+            &[from.bump],
+        ];
+        let signer_seeds = &[&miner_seeds[..]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.from_miner_vault.to_account_info(),
+                    to: ctx.accounts.to_miner_vault.to_account_info(),
+                    authority: from.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+    }
+
+    emit!(MinerTransferEvent {
+        quarry: ctx.accounts.quarry.key(),
+        from_authority: ctx.accounts.from_authority.key(),
+        to_authority: ctx.accounts.to_authority.key(),
+        balance,
+        timestamp: current_ts,
+    });
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::transfer_miner].
+#[derive(Accounts)]
+pub struct TransferMiner<'info> {
+    /// Authority of the [Miner] transferred from.
+    pub from_authority: Signer<'info>,
+
+    /// [Miner] transferred from.
+    #[account(mut)]
+    pub from_miner: Account<'info, Miner>,
+
+    /// Vault of the [Miner] transferred from.
+    #[account(mut)]
+    pub from_miner_vault: Account<'info, TokenAccount>,
+
+    /// Authority of the [Miner] transferred to.
+    pub to_authority: Signer<'info>,
+
+    /// [Miner] transferred to.
+    #[account(mut)]
+    pub to_miner: Account<'info, Miner>,
+
+    /// Vault of the [Miner] transferred to.
+    #[account(mut)]
+    pub to_miner_vault: Account<'info, TokenAccount>,
+
+    /// [Quarry] of both miners.
+    #[account(mut)]
+    pub quarry: Account<'info, Quarry>,
+
+    /// [Rewarder].
+    pub rewarder: Account<'info, Rewarder>,
+
+    /// Token program
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Validate<'info> for TransferMiner<'info> {
+    fn validate(&self) -> Result<()> {
+        self.quarry
+            .assert_not_paused(&self.rewarder, PAUSE_SCOPE_WITHDRAW)?;
+        assert_keys_eq!(self.quarry.rewarder, self.rewarder);
+        assert_keys_neq!(self.from_miner, self.to_miner);
+
+        assert_keys_eq!(self.from_authority, self.from_miner.authority);
+        assert_keys_eq!(self.from_miner.quarry, self.quarry);
+        assert_keys_eq!(self.from_miner.token_vault_key, self.from_miner_vault);
+
+        assert_keys_eq!(self.to_authority, self.to_miner.authority);
+        assert_keys_eq!(self.to_miner.quarry, self.quarry);
+        assert_keys_eq!(self.to_miner.token_vault_key, self.to_miner_vault);
+
+        Ok(())
+    }
+}
+
+/// Emitted when a [Miner]'s position is transferred to another [Miner].
+#[event]
+pub struct MinerTransferEvent {
+    /// [Quarry] of the miners.
+    #[index]
+    pub quarry: Pubkey,
+    /// Authority of the [Miner] transferred from.
+    #[index]
+    pub from_authority: Pubkey,
+    /// Authority of the [Miner] transferred to.
+    #[index]
+    pub to_authority: Pubkey,
+    /// Balance transferred.
+    pub balance: u64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
@@ -0,0 +1,198 @@
+//! Implementation of the [crate::quarry_mine::update_quarry_rewards_batch] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::update_quarry_rewards_batch] instruction.
+///
+/// The remaining accounts are the writable [Quarry]s to update.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateQuarryRewardsBatch<'info>>,
+) -> Result<()> {
+    invariant!(!ctx.remaining_accounts.is_empty(), InvalidRemainingAccounts);
+
+    let current_ts = Clock::get()?.unix_timestamp;
+    let rewarder = &mut ctx.accounts.rewarder;
+    let rewarder_key = rewarder.key();
+    update_quarries(rewarder_key, rewarder, ctx.remaining_accounts, current_ts)
+}
+
+/// Updates each [Quarry] of the [Rewarder] in `quarry_infos` up to `current_ts`.
+fn update_quarries(
+    rewarder_key: Pubkey,
+    rewarder: &mut Rewarder,
+    quarry_infos: &[AccountInfo],
+    current_ts: i64,
+) -> Result<()> {
+    for quarry_info in quarry_infos {
+        invariant!(quarry_info.is_writable, InvalidRemainingAccounts);
+        let mut quarry: Account<Quarry> = Account::try_from(quarry_info)?;
+        assert_keys_eq!(quarry.rewarder, rewarder_key);
+
+        let payroll = quarry.payroll(rewarder);
+        quarry.update_rewards_internal(current_ts, rewarder, &payroll)?;
+        quarry.sync_rewards_shares_generation(rewarder)?;
+        quarry.sync_rewards_epoch(rewarder)?;
+        quarry.report_rewards_totals(rewarder)?;
+
+        emit!(QuarryRewardsUpdateEvent {
+            token_mint: quarry.token_mint_key,
+            annual_rewards_rate: quarry.annual_rewards_rate,
+            rewards_share: quarry.rewards_share,
+            timestamp: current_ts,
+        });
+
+        // persist now so that a quarry supplied twice is read back updated
+        quarry.exit(&crate::ID)?;
+    }
+
+    Ok(())
+}
+
+/// Accounts for [quarry_mine::update_quarry_rewards_batch].
+#[derive(Accounts)]
+pub struct UpdateQuarryRewardsBatch<'info> {
+    /// [Rewarder].
+    #[account(mut)]
+    pub rewarder: Account<'info, Rewarder>,
+}
+
+impl<'info> Validate<'info> for UpdateQuarryRewardsBatch<'info> {
+    fn validate(&self) -> Result<()> {
+        self.rewarder.assert_not_paused()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const SECONDS_PER_DAY: i64 = 86_400;
+
+    fn quarry_data(quarry: &Quarry) -> Vec<u8> {
+        let mut data = Vec::with_capacity(8 + Quarry::LEN);
+        quarry.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    fn read_quarry(data: &[u8]) -> Quarry {
+        Quarry::try_deserialize(&mut &data[..]).unwrap()
+    }
+
+    #[test]
+    fn test_update_quarries() {
+        let rewarder_key = Pubkey::new_unique();
+        let rewarder = &mut Rewarder {
+            annual_rewards_rate: 1_000_000_000 * 365,
+            num_quarries: 3,
+            total_rewards_shares: 600,
+            ..Default::default()
+        };
+        let quarries: Vec<Quarry> = [100, 200, 300]
+            .into_iter()
+            .map(|rewards_share| Quarry {
+                rewarder: rewarder_key,
+                famine_ts: i64::MAX,
+                rewards_share,
+                annual_rewards_rate: rewarder
+                    .compute_quarry_annual_rewards_rate(rewards_share)
+                    .unwrap(),
+                total_tokens_deposited: 1_000_000,
+                ..Default::default()
+            })
+            .collect();
+
+        let keys: Vec<Pubkey> = quarries.iter().map(|_| Pubkey::new_unique()).collect();
+        let mut lamports = vec![0; quarries.len()];
+        let mut datas: Vec<Vec<u8>> = quarries.iter().map(quarry_data).collect();
+        let infos: Vec<AccountInfo> = keys
+            .iter()
+            .zip(lamports.iter_mut())
+            .zip(datas.iter_mut())
+            .map(|((key, lamports), data)| {
+                AccountInfo::new(key, false, true, lamports, data, &crate::ID, false, 0)
+            })
+            .collect();
+
+        update_quarries(rewarder_key, rewarder, &infos, SECONDS_PER_DAY).unwrap();
+        drop(infos);
+
+        for (quarry, data) in quarries.iter().zip(datas.iter()) {
+            let mut expected = *quarry;
+            expected
+                .update_rewards_internal(SECONDS_PER_DAY, rewarder, &quarry.payroll(rewarder))
+                .unwrap();
+            let updated = read_quarry(data);
+            assert_eq!(updated.last_update_ts, SECONDS_PER_DAY);
+            assert_ne!(updated.rewards_per_token_stored, 0);
+            assert_eq!(
+                updated.rewards_per_token_stored,
+                expected.rewards_per_token_stored
+            );
+        }
+        // the quarries earn in proportion to their shares
+        for (quarry, data) in quarries.iter().zip(datas.iter()) {
+            let expected = 1_000_000_000 * quarry.rewards_share / 600;
+            assert!(read_quarry(data).total_rewards_accrued.abs_diff(expected) <= 1);
+        }
+        assert_eq!(
+            rewarder.total_rewards_accrued,
+            quarries
+                .iter()
+                .zip(datas.iter())
+                .map(|(_, data)| read_quarry(data).total_rewards_accrued)
+                .sum::<u64>()
+        );
+    }
+
+    #[test]
+    fn test_update_quarries_rejects_invalid_quarries() {
+        let rewarder_key = Pubkey::new_unique();
+        let rewarder = &mut Rewarder {
+            annual_rewards_rate: 1_000_000_000 * 365,
+            num_quarries: 1,
+            total_rewards_shares: 100,
+            ..Default::default()
+        };
+        let quarry = Quarry {
+            rewarder: rewarder_key,
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            total_tokens_deposited: 1_000_000,
+            ..Default::default()
+        };
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+
+        // not writable
+        let mut data = quarry_data(&quarry);
+        let info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &crate::ID,
+            false,
+            0,
+        );
+        assert!(update_quarries(rewarder_key, rewarder, &[info], SECONDS_PER_DAY).is_err());
+        assert_eq!(data, quarry_data(&quarry));
+
+        // quarry of a different rewarder
+        let mut data = quarry_data(&quarry);
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &crate::ID,
+            false,
+            0,
+        );
+        assert!(update_quarries(Pubkey::new_unique(), rewarder, &[info], SECONDS_PER_DAY).is_err());
+        assert_eq!(data, quarry_data(&quarry));
+    }
+}
@@ -0,0 +1,194 @@
+//! Implementation of the [crate::quarry_mine::withdraw_tokens] instruction.
+
+use crate::*;
+
+/// Handler for the [crate::quarry_mine::withdraw_tokens] instruction.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, UserStake<'info>>,
+    amount: u64,
+) -> Result<()> {
+    ctx.accounts
+        .quarry
+        .assert_not_paused(&ctx.accounts.rewarder, PAUSE_SCOPE_WITHDRAW)?;
+    if amount == 0 {
+        // noop
+        return Ok(());
+    }
+    invariant!(ctx.accounts.quarry.unbonding_period == 0, UnbondingRequired);
+    invariant!(
+        amount <= ctx.accounts.miner_vault.amount,
+        InsufficientBalance
+    );
+
+    let clock = Clock::get()?;
+    let quarry = &mut ctx.accounts.quarry;
+    let penalty =
+        quarry.compute_early_exit_penalty(&ctx.accounts.miner, clock.unix_timestamp, amount)?;
+    quarry.process_stake_action_internal(
+        StakeAction::Withdraw,
+        clock.unix_timestamp,
+        &ctx.accounts.rewarder,
+        &mut ctx.accounts.miner,
+        amount,
+    )?;
+    let penalty = ctx.accounts.pay_early_exit_penalty(
+        ctx.remaining_accounts,
+        penalty,
+        clock.unix_timestamp,
+    )?;
+    checkpoints::record_balance_checkpoint(
+        &ctx.accounts.miner,
+        ctx.remaining_accounts,
+        clock.unix_timestamp,
+    )?;
+
+    // Transfer out LP tokens from quarry vault
+    let amount = unwrap_int!(amount.checked_sub(penalty));
+    ctx.accounts.transfer_from_vault(amount)?;
+
+    emit!(WithdrawEvent {
+        timestamp: clock.unix_timestamp,
+        authority: ctx.accounts.authority.key(),
+        amount,
+        token: ctx.accounts.token_account.mint,
+    });
+    Ok(())
+}
+
+impl<'info> UserStake<'info> {
+    /// Transfers tokens from the [Miner]'s vault to the user's token account.
+    pub(crate) fn transfer_from_vault(&self, amount: u64) -> Result<()> {
+        self.transfer_from_vault_to(self.token_account.to_account_info(), amount)
+    }
+
+    /// Transfers tokens from the [Miner]'s vault, signing as the [Miner].
+    fn transfer_from_vault_to(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let miner_seeds = &[
+            b"Miner".as_ref(),
+            self.miner.quarry.as_ref(),
+            self.miner.authority.as_ref(),
+            &[self.miner.bump],
+        ];
+        let signer_seeds = &[&miner_seeds[..]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.miner_vault.to_account_info(),
+                    to,
+                    authority: self.miner.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+
+    /// Pays an early exit penalty out of the [Miner]'s vault.
+    ///
+    /// The first remaining account must be the [Quarry::early_exit_treasury] if it is set.
+    /// Otherwise, if the [Quarry] stakes the rewards token, it must be the staked token [Mint],
+    /// from which the penalty is burned, or the [Rewarder::rewards_vault] if the [Rewarder] is
+    /// vault-funded. Other quarries hold the penalty in the [Quarry::early_exit_penalty_vault].
+    /// Returns the penalty charged.
+    pub(crate) fn pay_early_exit_penalty(
+        &mut self,
+        remaining_accounts: &[AccountInfo<'info>],
+        penalty: u64,
+        current_ts: i64,
+    ) -> Result<u64> {
+        if penalty == 0 {
+            return Ok(0);
+        }
+        let account = remaining_accounts.first();
+
+        if self
+            .quarry
+            .redistributes_early_exit_penalty_in_stake(&self.rewarder)
+        {
+            let penalty_vault: Account<TokenAccount> =
+                Account::try_from(unwrap_opt!(account, InvalidRemainingAccounts))?;
+            assert_keys_eq!(penalty_vault, self.quarry.early_exit_penalty_vault);
+            if !self
+                .quarry
+                .redistribute_early_exit_penalty_in_stake(penalty)?
+            {
+                return Ok(0);
+            }
+            self.transfer_from_vault_to(penalty_vault.to_account_info(), penalty)?;
+        } else if self.quarry.redistributes_early_exit_penalty() {
+            assert_keys_eq!(self.quarry.token_mint_key, self.rewarder.rewards_token_mint);
+            if !self.quarry.redistribute_early_exit_penalty(penalty)? {
+                return Ok(0);
+            }
+            if self.rewarder.is_vault_funded() {
+                // the penalty funds the rewards it is redistributed as
+                let rewards_vault: Account<TokenAccount> =
+                    Account::try_from(unwrap_opt!(account, InvalidRemainingAccounts))?;
+                assert_keys_eq!(rewards_vault, self.rewarder.rewards_vault);
+                self.transfer_from_vault_to(rewards_vault.to_account_info(), penalty)?;
+            } else {
+                let token_mint: Account<Mint> =
+                    Account::try_from(unwrap_opt!(account, InvalidRemainingAccounts))?;
+                assert_keys_eq!(token_mint, self.quarry.token_mint_key);
+
+                let miner_seeds = &[
+                    b"Miner".as_ref(),
+                    self.miner.quarry.as_ref(),
+                    self.miner.authority.as_ref(),
+                    &[self.miner.bump],
+                ];
+                let signer_seeds = &[&miner_seeds[..]];
+                token::burn(
+                    CpiContext::new_with_signer(
+                        self.token_program.to_account_info(),
+                        token::Burn {
+                            mint: token_mint.to_account_info(),
+                            from: self.miner_vault.to_account_info(),
+                            authority: self.miner.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    penalty,
+                )?;
+            }
+        } else {
+            let treasury: Account<TokenAccount> =
+                Account::try_from(unwrap_opt!(account, InvalidRemainingAccounts))?;
+            assert_keys_eq!(treasury, self.quarry.early_exit_treasury);
+            assert_keys_eq!(treasury.mint, self.quarry.token_mint_key);
+            self.transfer_from_vault_to(treasury.to_account_info(), penalty)?;
+        }
+
+        emit!(EarlyExitPenaltyEvent {
+            authority: self.authority.key(),
+            quarry: self.quarry.key(),
+            penalty,
+            treasury: self.quarry.early_exit_treasury,
+            timestamp: current_ts,
+        });
+
+        Ok(penalty)
+    }
+}
+
+/// Emitted when an early exit penalty is charged.
+#[event]
+pub struct EarlyExitPenaltyEvent {
+    /// Authority of the [Miner].
+    #[index]
+    pub authority: Pubkey,
+    /// [Quarry] of the [Miner].
+    #[index]
+    pub quarry: Pubkey,
+    /// Amount of staked tokens charged.
+    pub penalty: u64,
+    /// Treasury which received the penalty.
+    /// If default, the penalty was redistributed to the [Quarry]'s miners.
+    pub treasury: Pubkey,
+    /// When the event took place.
+    pub timestamp: i64,
+}
@@ -9,6 +9,7 @@
 //! This program is modeled after [Synthetix's StakingRewards.sol](https://github.com/Synthetixio/synthetix/blob/4b9b2ee09b38638de6fe1c38dbe4255a11ebed86/contracts/StakingRewards.sol).
 #![deny(rustdoc::all)]
 #![allow(rustdoc::missing_doc_code_examples)]
+#![allow(clippy::result_large_err)]
 #![allow(deprecated)]
 
 #[macro_use]
@@ -18,12 +19,14 @@ mod state;
 use anchor_lang::prelude::*;
 use anchor_spl::token::Token;
 use anchor_spl::token::{self, Mint, TokenAccount, Transfer};
-use payroll::Payroll;
 pub use state::*;
 use vipers::prelude::*;
 
 pub mod account_validators;
 pub mod addresses;
+pub mod allowlist;
+pub mod checkpoints;
+pub mod emission_schedule;
 pub mod payroll;
 pub mod quarry;
 pub mod rewarder;
@@ -45,6 +48,24 @@ pub const DEFAULT_CLAIM_FEE_MILLIBPS: u64 = 1_000;
 /// The maximum number of basis points possible.
 pub const MAX_BPS: u64 = 10_000;
 
+/// The maximum referral fee of a [Rewarder]: 10,000 milliBPS = 10 BPS or 0.1%.
+pub const MAX_REFERRAL_FEE_MILLIBPS: u64 = 10 * DEFAULT_CLAIM_FEE_MILLIBPS;
+
+/// The maximum lock boost a [Quarry] may grant: 40,000 BPS, or 5x the reward weight.
+pub const MAX_LOCK_BOOST_BPS: u64 = 4 * MAX_BPS;
+
+/// Pause scope of staking, including creating [Miner]s and locking tokens.
+pub const PAUSE_SCOPE_STAKE: u8 = 1 << 0;
+
+/// Pause scope of withdrawing, including unbonding and transferring [Miner]s.
+pub const PAUSE_SCOPE_WITHDRAW: u8 = 1 << 1;
+
+/// Pause scope of claiming rewards.
+pub const PAUSE_SCOPE_CLAIM: u8 = 1 << 2;
+
+/// All pause scopes.
+pub const PAUSE_SCOPE_ALL: u8 = PAUSE_SCOPE_STAKE | PAUSE_SCOPE_WITHDRAW | PAUSE_SCOPE_CLAIM;
+
 #[cfg(not(feature = "no-entrypoint"))]
 solana_security_txt::security_txt! {
     name: "Quarry Mine",
@@ -80,6 +101,16 @@ pub mod quarry_mine {
         instructions::new_rewarder_v2::handler(ctx)
     }
 
+    /// Creates a new [Rewarder] which pays rewards out of a token account
+    /// instead of minting them through a [quarry_mint_wrapper::MintWrapper].
+    ///
+    /// Rewards only accrue while the [Rewarder::rewards_vault] is funded:
+    /// see [quarry_mine::fund_rewards_vault].
+    #[access_control(ctx.accounts.validate())]
+    pub fn new_vault_funded_rewarder(ctx: Context<NewVaultFundedRewarder>) -> Result<()> {
+        instructions::new_vault_funded_rewarder::handler(ctx)
+    }
+
     /// Sets the pause authority.
     #[access_control(ctx.accounts.validate())]
     pub fn set_pause_authority(ctx: Context<SetPauseAuthority>) -> Result<()> {
@@ -104,6 +135,24 @@ pub mod quarry_mine {
         Ok(())
     }
 
+    /// Sets the [Rewarder::paused_scopes], pausing actions on all [Quarry]s.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_paused_scopes(
+        ctx: Context<MutableRewarderWithPauseAuthority>,
+        paused_scopes: u8,
+    ) -> Result<()> {
+        instructions::set_paused_scopes::handler(ctx, paused_scopes)
+    }
+
+    /// Sets the [Quarry::paused_scopes], pausing actions on a single [Quarry].
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_quarry_paused_scopes(
+        ctx: Context<SetQuarryPausedScopes>,
+        paused_scopes: u8,
+    ) -> Result<()> {
+        instructions::set_paused_scopes::quarry_handler(ctx, paused_scopes)
+    }
+
     /// Transfers the [Rewarder] authority to a different account.
     #[access_control(ctx.accounts.validate())]
     pub fn transfer_authority(
@@ -126,6 +175,12 @@ pub mod quarry_mine {
     }
 
     /// Sets the amount of reward tokens distributed to all [Quarry]s per day.
+    ///
+    /// For a vault-funded [Rewarder], this starts a new rewards epoch: the remaining funding is
+    /// rescaled to the new rate, and each [Quarry] accrues at the previous rate up to now the
+    /// next time it is updated. This fails until every [Quarry] which has not been closed has
+    /// been updated with [quarry_mine::update_quarry_rewards] or
+    /// [quarry_mine::update_quarry_rewards_batch] since the previous epoch.
     #[access_control(ctx.accounts.validate())]
     pub fn set_annual_rewards(ctx: Context<SetAnnualRewards>, new_rate: u64) -> Result<()> {
         invariant!(
@@ -134,9 +189,9 @@ pub mod quarry_mine {
         );
         let rewarder = &mut ctx.accounts.auth.rewarder;
         let previous_rate = rewarder.annual_rewards_rate;
-        rewarder.annual_rewards_rate = new_rate;
-
         let current_ts = Clock::get()?.unix_timestamp;
+        rewarder.set_annual_rewards_rate(new_rate, current_ts)?;
+
         emit!(RewarderAnnualRewardsUpdateEvent {
             previous_rate,
             new_rate,
@@ -146,6 +201,91 @@ pub mod quarry_mine {
         Ok(())
     }
 
+    /// Sets the [EmissionSchedule] applied to the annual rewards rate.
+    /// An empty schedule keeps the rate constant.
+    ///
+    /// Quarries accrue against the schedule stored on the [Rewarder], so the new schedule
+    /// must have the same multiplier as the current one up to the current time.
+    /// To change the rest of a schedule, keep its points up to now and add a point
+    /// with the current multiplier at or after now.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_emission_schedule(
+        ctx: Context<SetEmissionSchedule>,
+        points: Vec<EmissionPoint>,
+    ) -> Result<()> {
+        let current_ts = Clock::get()?.unix_timestamp;
+        let rewarder = &mut ctx.accounts.auth.rewarder;
+        let emission_schedule = EmissionSchedule::try_from_points(&points)?;
+        invariant!(
+            unwrap_int!(rewarder
+                .emission_schedule
+                .agrees_until_unsafe(&emission_schedule, current_ts)),
+            EmissionScheduleChangesPast
+        );
+        rewarder.emission_schedule = emission_schedule;
+
+        emit!(RewarderEmissionScheduleUpdateEvent {
+            points,
+            timestamp: current_ts,
+        });
+
+        Ok(())
+    }
+
+    /// Sets the claim fee of a [Rewarder], which may not exceed its maximum claim fee.
+    /// This may only be called by the [addresses::FEE_SETTER].
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_claim_fee(ctx: Context<SetClaimFee>, claim_fee_millibps: u64) -> Result<()> {
+        instructions::set_claim_fee::handler(ctx, claim_fee_millibps)
+    }
+
+    /// Lowers the maximum claim fee of a [Rewarder]. The maximum may never be raised.
+    /// This may only be called by the [Rewarder]::authority.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_max_claim_fee(
+        ctx: Context<SetMaxClaimFee>,
+        max_claim_fee_millibps: u64,
+    ) -> Result<()> {
+        instructions::set_max_claim_fee::handler(ctx, max_claim_fee_millibps)
+    }
+
+    /// Sets the referral fee charged on top of the claim fee in
+    /// [quarry_mine::claim_rewards_with_referrer] and
+    /// [quarry_mine::claim_rewards_from_vault_with_referrer], up to [MAX_REFERRAL_FEE_MILLIBPS].
+    /// This may only be called by the [addresses::FEE_SETTER].
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_referral_fee(
+        ctx: Context<SetReferralFee>,
+        referral_fee_millibps: u64,
+    ) -> Result<()> {
+        instructions::set_referral_fee::handler(ctx, referral_fee_millibps)
+    }
+
+    /// Adds rewards to the [Rewarder::rewards_vault] of a vault-funded [Rewarder],
+    /// extending [Rewarder::funded_until_ts] at the current annual rewards rate.
+    /// Anyone can call this.
+    ///
+    /// If the vault has run out, rewards resume from now in a new rewards epoch,
+    /// which fails until every [Quarry] has been synchronized with the previous one.
+    #[access_control(ctx.accounts.validate())]
+    pub fn fund_rewards_vault(ctx: Context<FundRewardsVault>, amount: u64) -> Result<()> {
+        instructions::fund_rewards_vault::handler(ctx, amount)
+    }
+
+    /// Sends the rewards left in the [Rewarder::rewards_vault] after it has run out,
+    /// less those still owed to [Miner]s, to a token account.
+    /// This may only be called by the [Rewarder]::authority.
+    ///
+    /// The rewards owed are read from the totals of the [Rewarder], which are only final once
+    /// every [Quarry] has been updated after the vault ran out. Until then, this starts a
+    /// new rewards epoch if needed and sweeps nothing; sweep again once every [Quarry] which
+    /// has not been closed has been updated with [quarry_mine::update_quarry_rewards] or
+    /// [quarry_mine::update_quarry_rewards_batch].
+    #[access_control(ctx.accounts.validate())]
+    pub fn sweep_rewards_vault(ctx: Context<SweepRewardsVault>) -> Result<()> {
+        instructions::sweep_rewards_vault::handler(ctx)
+    }
+
     // --------------------------------
     // Quarry functions
     // --------------------------------
@@ -168,9 +308,14 @@ pub mod quarry_mine {
     }
 
     /// Sets the rewards share of a quarry.
+    ///
+    /// The new share takes effect on each quarry the next time it is updated. To change the
+    /// shares of several quarries atomically, use [quarry_mine::stage_rewards_share] and
+    /// [quarry_mine::commit_rewards_shares] instead.
     #[access_control(ctx.accounts.validate())]
     pub fn set_rewards_share(ctx: Context<SetRewardsShare>, new_share: u64) -> Result<()> {
         let rewarder = &mut ctx.accounts.auth.rewarder;
+        invariant!(!rewarder.has_pending_rewards_shares(), RewardsSharesStaged);
         let quarry = &mut ctx.accounts.quarry;
         rewarder.total_rewards_shares = unwrap_int!(rewarder
             .total_rewards_shares
@@ -181,12 +326,57 @@ pub mod quarry_mine {
         // Do not update annual_rewards_rate here. Just wait for the update_quarry_rewards call
         // because rewarder.total_rewards_shares may change with other quarry share changes
         // and quarry.annual_rewards_rate calculated here will become invalid.
-        // The correct share update procedure is calling all set_rewards_share
-        // first (with set_annual_rewards if needed) and update_quarry_rewards
-        // for each quarry to finalize changes.
-        // TODO: because update_quarry_rewards is permissionless better
-        // to add protection from calling it after admin started to change the rates
-        // and commit_changes instruction making possible to call update_quarry_rewards again
+
+        Ok(())
+    }
+
+    /// Stages a new rewards share for a quarry.
+    ///
+    /// Staged shares have no effect until [quarry_mine::commit_rewards_shares] is called,
+    /// so quarries may be updated in between without observing a partial set of shares.
+    #[access_control(ctx.accounts.validate())]
+    pub fn stage_rewards_share(ctx: Context<SetRewardsShare>, new_share: u64) -> Result<()> {
+        let current_ts = Clock::get()?.unix_timestamp;
+        let rewarder = &mut ctx.accounts.auth.rewarder;
+        let quarry = &mut ctx.accounts.quarry;
+
+        // apply the previous commit before staging on top of it
+        let payroll = quarry.payroll(rewarder);
+        quarry.update_rewards_internal(current_ts, rewarder, &payroll)?;
+        quarry.sync_rewards_shares_generation(rewarder)?;
+        quarry.sync_rewards_epoch(rewarder)?;
+        quarry.report_rewards_totals(rewarder)?;
+
+        rewarder.stage_rewards_share(quarry, new_share)?;
+
+        emit!(QuarryRewardsShareStageEvent {
+            token_mint: quarry.token_mint_key,
+            rewards_share: quarry.rewards_share,
+            pending_rewards_share: new_share,
+            timestamp: current_ts,
+        });
+
+        Ok(())
+    }
+
+    /// Commits all staged rewards shares.
+    ///
+    /// Every quarry must have been synchronized with [quarry_mine::update_quarry_rewards]
+    /// since the previous commit. Each quarry switches to its new rate at the commit time,
+    /// regardless of when it is next updated.
+    #[access_control(ctx.accounts.validate())]
+    pub fn commit_rewards_shares(ctx: Context<CommitRewardsShares>) -> Result<()> {
+        let current_ts = Clock::get()?.unix_timestamp;
+        let rewarder = &mut ctx.accounts.auth.rewarder;
+        let num_staged_quarries = rewarder.num_staged_quarries;
+        rewarder.commit_rewards_shares(current_ts)?;
+
+        emit!(RewardsSharesCommitEvent {
+            total_rewards_shares: rewarder.total_rewards_shares,
+            num_staged_quarries,
+            rewards_shares_generation: rewarder.rewards_shares_generation,
+            timestamp: current_ts,
+        });
 
         Ok(())
     }
@@ -200,15 +390,132 @@ pub mod quarry_mine {
         Ok(())
     }
 
+    /// Sets the time at which a [Quarry] starts accruing rewards.
+    /// [Miner]s may stake before then, but earn nothing until it.
+    /// This may only be called by the [Rewarder]::authority.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_start_ts(ctx: Context<SetStartTs>, start_ts: i64) -> Result<()> {
+        instructions::set_start_ts::handler(ctx, start_ts)
+    }
+
+    /// Adds a secondary [RewardStream] to a [Quarry].
+    /// The [Rewarder] must be a [quarry_mint_wrapper::Minter] of the stream's mint wrapper.
+    /// This may only be called by the [Rewarder]::authority.
+    #[access_control(ctx.accounts.validate())]
+    pub fn add_reward_stream(ctx: Context<AddRewardStream>) -> Result<()> {
+        instructions::add_reward_stream::handler(ctx)
+    }
+
+    /// Sets the annual rewards rate and famine of a [RewardStream].
+    /// This may only be called by the [Rewarder]::authority.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_reward_stream(
+        ctx: Context<SetRewardStream>,
+        index: u8,
+        annual_rewards_rate: u64,
+        famine_ts: i64,
+    ) -> Result<()> {
+        instructions::set_reward_stream::handler(ctx, index, annual_rewards_rate, famine_ts)
+    }
+
+    /// Sets the maximum lock duration and boost of a [Quarry].
+    /// A zero `max_lock_duration` disables new locks.
+    /// This may only be called by the [Rewarder]::authority.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_lock_params(
+        ctx: Context<SetLockParams>,
+        max_lock_duration: i64,
+        max_lock_boost_bps: u64,
+    ) -> Result<()> {
+        instructions::set_lock_params::handler(ctx, max_lock_duration, max_lock_boost_bps)
+    }
+
+    /// Sets the unbonding period of a [Quarry].
+    /// While nonzero, tokens must be withdrawn with [quarry_mine::request_unbond]
+    /// and [quarry_mine::complete_unbond].
+    /// This may only be called by the [Rewarder]::authority.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_unbonding_period(
+        ctx: Context<SetUnbondingPeriod>,
+        unbonding_period: i64,
+    ) -> Result<()> {
+        instructions::set_unbonding_period::handler(ctx, unbonding_period)
+    }
+
+    /// Sets the Merkle root of the authorities allowed to stake into a [Quarry].
+    /// A zero root opens the [Quarry] to all.
+    /// Existing [Miner]s must be approved against a new root with [quarry_mine::approve_miner].
+    /// This may only be called by the [Rewarder]::authority.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_allowlist_root(
+        ctx: Context<SetAllowlistRoot>,
+        allowlist_root: [u8; 32],
+    ) -> Result<()> {
+        instructions::set_allowlist_root::handler(ctx, allowlist_root)
+    }
+
+    /// Sets the maximum [Quarry::total_tokens_deposited] and [Miner::balance] of a [Quarry].
+    /// A zero cap is unlimited. Stakes which would exceed either cap fail.
+    /// This may only be called by the [Rewarder]::authority.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_deposit_caps(
+        ctx: Context<SetDepositCaps>,
+        max_total_tokens_deposited: u64,
+        max_miner_balance: u64,
+    ) -> Result<()> {
+        instructions::set_deposit_caps::handler(ctx, max_total_tokens_deposited, max_miner_balance)
+    }
+
+    /// Sets the early exit penalty of a [Quarry].
+    /// Penalties are redistributed to the remaining miners if `treasury` is the default key.
+    /// If the [Quarry] does not stake the rewards token, they are redistributed in the staked
+    /// token, held in a token account owned by the [Quarry] which must be supplied as a
+    /// remaining account, and claimed with [quarry_mine::claim_early_exit_penalties].
+    /// This may only be called by the [Rewarder]::authority.
+    #[access_control(ctx.accounts.validate())]
+    pub fn set_early_exit_penalty<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetEarlyExitPenalty<'info>>,
+        early_exit_period: i64,
+        early_exit_penalty_bps: u64,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        instructions::set_early_exit_penalty::handler(
+            ctx,
+            early_exit_period,
+            early_exit_penalty_bps,
+            treasury,
+        )
+    }
+
+    /// Claims the early exit penalties redistributed to a [Miner] in the staked token.
+    #[access_control(ctx.accounts.validate())]
+    pub fn claim_early_exit_penalties(ctx: Context<ClaimEarlyExitPenalties>) -> Result<()> {
+        instructions::claim_early_exit_penalties::handler(ctx)
+    }
+
+    /// Closes a [Quarry] which has no miners, deposits, rewards share or unclaimed early exit
+    /// penalties, after reporting its rewards to the [Rewarder].
+    /// This may only be called by the [Rewarder]::authority.
+    ///
+    /// If the [Quarry] has an [Quarry::early_exit_penalty_vault], it must be supplied
+    /// as the first remaining account.
+    #[access_control(ctx.accounts.validate())]
+    pub fn close_quarry<'info>(ctx: Context<'_, '_, '_, 'info, CloseQuarry<'info>>) -> Result<()> {
+        instructions::close_quarry::handler(ctx)
+    }
+
     /// Synchronizes quarry rewards with the rewarder.
     /// Anyone can call this.
     #[access_control(ctx.accounts.validate())]
     pub fn update_quarry_rewards(ctx: Context<UpdateQuarryRewards>) -> Result<()> {
         let current_ts = Clock::get()?.unix_timestamp;
-        let rewarder = &ctx.accounts.rewarder;
-        let payroll: Payroll = (*ctx.accounts.quarry).into();
+        let rewarder = &mut ctx.accounts.rewarder;
         let quarry = &mut ctx.accounts.quarry;
+        let payroll = quarry.payroll(rewarder);
         quarry.update_rewards_internal(current_ts, rewarder, &payroll)?;
+        quarry.sync_rewards_shares_generation(rewarder)?;
+        quarry.sync_rewards_epoch(rewarder)?;
+        quarry.report_rewards_totals(rewarder)?;
 
         emit!(QuarryRewardsUpdateEvent {
             token_mint: quarry.token_mint_key,
@@ -220,9 +527,19 @@ pub mod quarry_mine {
         Ok(())
     }
 
-    /// --------------------------------
-    /// Miner functions
-    /// --------------------------------
+    /// Synchronizes the rewards of many quarries with the rewarder.
+    /// The quarries are supplied as writable remaining accounts.
+    /// Anyone can call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn update_quarry_rewards_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateQuarryRewardsBatch<'info>>,
+    ) -> Result<()> {
+        instructions::update_quarry_rewards_batch::handler(ctx)
+    }
+
+    // --------------------------------
+    // Miner functions
+    // --------------------------------
 
     /// Creates a [Miner] for the given authority.
     ///
@@ -230,7 +547,7 @@ pub mod quarry_mine {
     #[deprecated(since = "5.0.0", note = "Use `create_miner_v2` instead.")]
     #[access_control(ctx.accounts.validate())]
     pub fn create_miner(ctx: Context<CreateMiner>, _bump: u8) -> Result<()> {
-        instructions::create_miner::handler(ctx)
+        instructions::create_miner::handler(ctx, &[])
     }
 
     /// Creates a [Miner] for the given authority.
@@ -240,7 +557,22 @@ pub mod quarry_mine {
     /// The V2 variant removes the need for supplying the bump.
     #[access_control(ctx.accounts.validate())]
     pub fn create_miner_v2(ctx: Context<CreateMiner>) -> Result<()> {
-        instructions::create_miner::handler(ctx)
+        instructions::create_miner::handler(ctx, &[])
+    }
+
+    /// Creates a [Miner] for the given authority on a [Quarry] with an allowlist.
+    ///
+    /// The proof shows that the authority is in the [Quarry::allowlist_root].
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_miner_with_proof(ctx: Context<CreateMiner>, proof: Vec<[u8; 32]>) -> Result<()> {
+        instructions::create_miner::handler(ctx, &proof)
+    }
+
+    /// Approves a [Miner] against the current [Quarry::allowlist_root],
+    /// after the root has been rotated.
+    #[access_control(ctx.accounts.validate())]
+    pub fn approve_miner(ctx: Context<ApproveMiner>, proof: Vec<[u8; 32]>) -> Result<()> {
+        instructions::approve_miner::handler(ctx, &proof)
     }
 
     /// Claims rewards for the [Miner].
@@ -258,9 +590,88 @@ pub mod quarry_mine {
         instructions::claim_rewards_v2::handler(ctx)
     }
 
+    /// Claims rewards for the [Miner], minting the [Rewarder]'s referral fee out of the
+    /// claimed rewards to the referrer's token account.
+    /// The referrer may not be the [Miner]'s authority.
+    #[access_control(ctx.accounts.validate())]
+    pub fn claim_rewards_with_referrer(ctx: Context<ClaimRewardsWithReferrer>) -> Result<()> {
+        instructions::claim_rewards_with_referrer::handler(ctx)
+    }
+
+    /// Claims rewards for the [Miner] and stakes them into the same authority's [Miner]
+    /// on the [Quarry] of the rewards token, in one step.
+    ///
+    /// If the staking [Miner] has [Miner::checkpoints], they must be supplied as a remaining account.
+    #[access_control(ctx.accounts.validate())]
+    pub fn claim_and_restake<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimAndRestake<'info>>,
+    ) -> Result<()> {
+        instructions::claim_and_restake::handler(ctx)
+    }
+
+    /// Claims rewards for the [Miner] from the [Rewarder::rewards_vault] of a vault-funded [Rewarder].
+    #[access_control(ctx.accounts.validate())]
+    pub fn claim_rewards_from_vault(ctx: Context<ClaimRewardsFromVault>) -> Result<()> {
+        instructions::claim_rewards_from_vault::handler(ctx)
+    }
+
+    /// Claims rewards for the [Miner] from the [Rewarder::rewards_vault], sending the
+    /// [Rewarder]'s referral fee out of the claimed rewards to the referrer's token account.
+    /// The referrer may not be the [Miner]'s authority.
+    #[access_control(ctx.accounts.validate())]
+    pub fn claim_rewards_from_vault_with_referrer(
+        ctx: Context<ClaimRewardsFromVaultWithReferrer>,
+    ) -> Result<()> {
+        instructions::claim_rewards_from_vault_with_referrer::handler(ctx)
+    }
+
+    /// Claims the rewards of one or more of the [Quarry]'s [RewardStream]s for the [Miner].
+    ///
+    /// Each stream is claimed by supplying its
+    /// `[mint_wrapper, minter, rewards_token_mint, rewards_token_account]` as remaining accounts.
+    #[access_control(ctx.accounts.validate())]
+    pub fn claim_reward_streams<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimRewardStreams<'info>>,
+    ) -> Result<()> {
+        instructions::claim_reward_streams::handler(ctx)
+    }
+
+    /// Computes the rewards claimable by a [Miner] at the current time without modifying any
+    /// account, and returns them as a serialized [ClaimableRewards] via `set_return_data`.
+    /// This may be simulated or called via CPI.
+    #[access_control(ctx.accounts.validate())]
+    pub fn get_claimable_rewards(ctx: Context<GetClaimableRewards>) -> Result<()> {
+        instructions::get_claimable_rewards::handler(ctx)
+    }
+
+    /// Creates [MinerCheckpoints] for a [Miner], recording the history of its balance.
+    ///
+    /// Once created, the [MinerCheckpoints] must be supplied as a remaining account
+    /// to every instruction which changes the [Miner::balance].
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_miner_checkpoints(ctx: Context<CreateMinerCheckpoints>) -> Result<()> {
+        instructions::create_miner_checkpoints::handler(ctx)
+    }
+
+    /// Returns the [Miner::balance] as of `timestamp` as a serialized [u64]
+    /// via `set_return_data`, for use as voting power.
+    /// This may be simulated or called via CPI.
+    #[access_control(ctx.accounts.validate())]
+    pub fn get_balance_at(ctx: Context<GetBalanceAt>, timestamp: i64) -> Result<()> {
+        instructions::get_balance_at::handler(ctx, timestamp)
+    }
+
     /// Stakes tokens into the [Miner].
+    ///
+    /// If the [Miner] has [Miner::checkpoints], they must be supplied as a remaining account.
     #[access_control(ctx.accounts.validate())]
-    pub fn stake_tokens(ctx: Context<UserStake>, amount: u64) -> Result<()> {
+    pub fn stake_tokens<'info>(
+        ctx: Context<'_, '_, '_, 'info, UserStake<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .quarry
+            .assert_not_paused(&ctx.accounts.rewarder, PAUSE_SCOPE_STAKE)?;
         if amount == 0 {
             // noop
             return Ok(());
@@ -275,6 +686,11 @@ pub mod quarry_mine {
             &mut ctx.accounts.miner,
             amount,
         )?;
+        checkpoints::record_balance_checkpoint(
+            &ctx.accounts.miner,
+            ctx.remaining_accounts,
+            clock.unix_timestamp,
+        )?;
 
         let cpi_accounts = Transfer {
             from: ctx.accounts.token_account.to_account_info(),
@@ -295,56 +711,113 @@ pub mod quarry_mine {
         Ok(())
     }
 
+    /// Stakes tokens of the depositor into the [Miner] of another authority,
+    /// which does not need to sign. Only the [Miner::authority] may withdraw them.
+    ///
+    /// Unless the depositor is the [Miner::authority], this does not restart the [Miner]'s
+    /// early exit period. Instead, the deposited tokens are added to [Miner::staked_for_balance]
+    /// and penalized on withdrawal until the early exit period of the latest such deposit is over.
+    /// The tokens count towards the [Quarry]'s deposit caps.
+    /// If the [Miner] has [Miner::checkpoints], they must be supplied as a remaining account.
+    #[access_control(ctx.accounts.validate())]
+    pub fn stake_tokens_for<'info>(
+        ctx: Context<'_, '_, '_, 'info, StakeTokensFor<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::stake_tokens_for::handler(ctx, amount)
+    }
+
     /// Withdraws tokens from the [Miner].
+    ///
+    /// If the [Quarry] charges an early exit penalty, the
+    /// [Quarry::early_exit_treasury], [Rewarder::rewards_vault], staked token [Mint] or
+    /// [Quarry::early_exit_penalty_vault] must be supplied as the first remaining account, followed by the [Miner::checkpoints]
+    /// if the [Miner] has any.
     #[access_control(ctx.accounts.validate())]
-    pub fn withdraw_tokens(ctx: Context<UserStake>, amount: u64) -> Result<()> {
-        if amount == 0 {
-            // noop
-            return Ok(());
-        }
-        invariant!(
-            amount <= ctx.accounts.miner_vault.amount,
-            InsufficientBalance
-        );
+    pub fn withdraw_tokens<'info>(
+        ctx: Context<'_, '_, '_, 'info, UserStake<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::withdraw_tokens::handler(ctx, amount)
+    }
 
-        let clock = Clock::get()?;
-        let quarry = &mut ctx.accounts.quarry;
-        quarry.process_stake_action_internal(
-            StakeAction::Withdraw,
-            clock.unix_timestamp,
-            &ctx.accounts.rewarder,
-            &mut ctx.accounts.miner,
-            amount,
-        )?;
+    /// Withdraws the entire vault of the [Miner] while the [Rewarder] or [Quarry] pauses
+    /// withdrawals, bypassing its lock, unbonding period and early exit penalty.
+    /// The [Miner]'s unclaimed rewards are forfeited.
+    ///
+    /// If the [Miner] has [Miner::checkpoints], they must be supplied as a remaining account.
+    #[access_control(ctx.accounts.validate_accounts())]
+    pub fn emergency_withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, UserStake<'info>>,
+    ) -> Result<()> {
+        instructions::emergency_withdraw::handler(ctx)
+    }
 
-        // Sign a transfer instruction as the [Miner]
-        let miner_seeds = &[
-            b"Miner".as_ref(),
-            ctx.accounts.miner.quarry.as_ref(),
-            ctx.accounts.miner.authority.as_ref(),
-            &[ctx.accounts.miner.bump],
-        ];
-        let signer_seeds = &[&miner_seeds[..]];
-        let cpi_accounts = token::Transfer {
-            from: ctx.accounts.miner_vault.to_account_info(),
-            to: ctx.accounts.token_account.to_account_info(),
-            authority: ctx.accounts.miner.to_account_info(),
-        };
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            cpi_accounts,
-            signer_seeds,
-        );
-        // Transfer out LP tokens from quarry vault
-        token::transfer(cpi_ctx, amount)?;
+    /// Requests to withdraw tokens from the [Miner] after the [Quarry]'s unbonding period.
+    /// The tokens stop earning rewards immediately.
+    ///
+    /// If the [Miner] has [Miner::checkpoints], they must be supplied as a remaining account.
+    #[access_control(ctx.accounts.validate())]
+    pub fn request_unbond<'info>(
+        ctx: Context<'_, '_, '_, 'info, RequestUnbond<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::request_unbond::handler(ctx, amount)
+    }
 
-        emit!(WithdrawEvent {
-            timestamp: clock.unix_timestamp,
-            authority: ctx.accounts.authority.key(),
-            amount,
-            token: ctx.accounts.token_account.mint,
-        });
-        Ok(())
+    /// Withdraws the unbonded tokens of the [Miner] once the unbonding period has elapsed.
+    /// Early exit penalties are settled as in [quarry_mine::withdraw_tokens].
+    #[access_control(ctx.accounts.validate())]
+    pub fn complete_unbond<'info>(ctx: Context<'_, '_, '_, 'info, UserStake<'info>>) -> Result<()> {
+        instructions::complete_unbond::handler(ctx)
+    }
+
+    /// Closes an empty [Miner] and its vault.
+    /// Any tokens left in the vault are returned to the authority. If the [Miner] records
+    /// [MinerCheckpoints], they must be supplied as a writable remaining account and are closed too.
+    #[access_control(ctx.accounts.validate())]
+    pub fn close_miner<'info>(ctx: Context<'_, '_, '_, 'info, CloseMiner<'info>>) -> Result<()> {
+        instructions::close_miner::handler(ctx)
+    }
+
+    /// Transfers the position of a [Miner] to the [Miner] of another authority in the same [Quarry].
+    /// Both authorities must sign, and the destination [Miner] must be empty.
+    ///
+    /// The [Miner::checkpoints] of either [Miner] must be supplied as remaining accounts.
+    #[access_control(ctx.accounts.validate())]
+    pub fn transfer_miner<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferMiner<'info>>,
+    ) -> Result<()> {
+        instructions::transfer_miner::handler(ctx)
+    }
+
+    /// Moves `amount` of staked tokens from a [Miner] to the [Miner] of the same authority
+    /// on another [Quarry] of the same staked token, without leaving the vaults.
+    ///
+    /// The source [Quarry] must not have an unbonding period or charge an early exit penalty
+    /// on the tokens. The tokens keep the [Miner::last_stake_ts] of the source [Miner], so the
+    /// early exit period of the destination [Miner] is not restarted.
+    /// The [Miner::checkpoints] of either [Miner] must be supplied as remaining accounts.
+    #[access_control(ctx.accounts.validate())]
+    pub fn migrate_stake<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateStake<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::migrate_stake::handler(ctx, amount)
+    }
+
+    /// Locks the [Miner]'s balance for `lock_duration` seconds in exchange for a reward boost.
+    /// Locked tokens may not be withdrawn until the lock ends.
+    #[access_control(ctx.accounts.validate())]
+    pub fn lock_tokens(ctx: Context<LockTokens>, lock_duration: i64) -> Result<()> {
+        instructions::lock_tokens::handler(ctx, lock_duration)
+    }
+
+    /// Removes the boost of a [Miner] whose lock has ended.
+    /// Anyone can call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn expire_lock(ctx: Context<ExpireLock>) -> Result<()> {
+        instructions::expire_lock::handler(ctx)
     }
 
     /// Withdraw tokens from a [Miner]-owned token account that is not the [Miner::token_vault_key].
@@ -383,11 +856,19 @@ pub mod quarry_mine {
 
         Ok(())
     }
+
+    /// Reallocates a [Rewarder], [Quarry] or [Miner] created by a previous version
+    /// of the program to the current account size.
+    /// Anyone can call this.
+    #[access_control(ctx.accounts.validate())]
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        instructions::migrate_account::handler(ctx)
+    }
 }
 
-/// --------------------------------
-/// Context Structs
-/// --------------------------------
+// --------------------------------
+// Context Structs
+// --------------------------------
 
 /* Rewarder contexts */
 
@@ -453,6 +934,13 @@ pub struct SetAnnualRewards<'info> {
     pub auth: MutableRewarderWithAuthority<'info>,
 }
 
+/// Accounts for [quarry_mine::set_emission_schedule].
+#[derive(Accounts)]
+pub struct SetEmissionSchedule<'info> {
+    /// [Rewarder],
+    pub auth: MutableRewarderWithAuthority<'info>,
+}
+
 /* Quarry contexts */
 
 /// Accounts for [quarry_mine::set_famine].
@@ -477,6 +965,13 @@ pub struct SetRewardsShare<'info> {
     pub quarry: Account<'info, Quarry>,
 }
 
+/// Accounts for [quarry_mine::commit_rewards_shares].
+#[derive(Accounts)]
+pub struct CommitRewardsShares<'info> {
+    /// [Rewarder].
+    pub auth: MutableRewarderWithAuthority<'info>,
+}
+
 /// Accounts for [quarry_mine::update_quarry_rewards].
 #[derive(Accounts)]
 pub struct UpdateQuarryRewards<'info> {
@@ -485,6 +980,7 @@ pub struct UpdateQuarryRewards<'info> {
     pub quarry: Account<'info, Quarry>,
 
     /// [Rewarder].
+    #[account(mut)]
     pub rewarder: Account<'info, Rewarder>,
 }
 
@@ -544,7 +1040,7 @@ pub struct ExtractFees<'info> {
     pub token_program: Program<'info, Token>,
 }
 
-/// Accounts for [quarry_mine::pause] and [quarry_mine::unpause].
+/// Accounts for [quarry_mine::pause], [quarry_mine::unpause] and [quarry_mine::set_paused_scopes].
 #[derive(Accounts)]
 pub struct MutableRewarderWithPauseAuthority<'info> {
     /// Pause authority of the rewarder.
@@ -600,6 +1096,15 @@ pub struct RewarderAnnualRewardsUpdateEvent {
     pub timestamp: i64,
 }
 
+/// Emitted when the emission schedule is updated.
+#[event]
+pub struct RewarderEmissionScheduleUpdateEvent {
+    /// Points of the new schedule.
+    pub points: Vec<EmissionPoint>,
+    /// When the event took place.
+    pub timestamp: i64,
+}
+
 /// Emitted when a quarry's reward rate is updated.
 #[event]
 pub struct QuarryRewardsUpdateEvent {
@@ -613,6 +1118,32 @@ pub struct QuarryRewardsUpdateEvent {
     pub timestamp: i64,
 }
 
+/// Emitted when a quarry's rewards share is staged.
+#[event]
+pub struct QuarryRewardsShareStageEvent {
+    /// [Mint] of the [Quarry] token.
+    pub token_mint: Pubkey,
+    /// Current rewards share.
+    pub rewards_share: u64,
+    /// Rewards share applied on the next commit.
+    pub pending_rewards_share: u64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
+
+/// Emitted when staged rewards shares are committed.
+#[event]
+pub struct RewardsSharesCommitEvent {
+    /// New total rewards shares.
+    pub total_rewards_shares: u64,
+    /// Number of quarries whose shares were committed.
+    pub num_staged_quarries: u16,
+    /// Generation of the commit.
+    pub rewards_shares_generation: u64,
+    /// When the event took place.
+    pub timestamp: i64,
+}
+
 /// Error Codes
 #[error_code]
 pub enum ErrorCode {
@@ -640,4 +1171,80 @@ pub enum ErrorCode {
     Paused,
     #[msg("Rewards earned exceeded quarry's upper bound.")]
     UpperboundExceeded,
+    #[msg("Quarry already has the maximum number of reward streams.")]
+    MaxRewardStreamsExceeded,
+    #[msg("Reward stream already exists.")]
+    RewardStreamAlreadyExists,
+    #[msg("Reward stream not found.")]
+    RewardStreamNotFound,
+    #[msg("Invalid remaining accounts.")]
+    InvalidRemainingAccounts,
+    #[msg("Miner tokens are locked.")]
+    MinerLocked,
+    #[msg("Invalid lock duration.")]
+    InvalidLockDuration,
+    #[msg("Max lock boost exceeded.")]
+    MaxLockBoostExceeded,
+    #[msg("Lock has not expired.")]
+    LockNotExpired,
+    #[msg("Invalid emission schedule.")]
+    InvalidEmissionSchedule,
+    #[msg("Quarry has not been synchronized with the latest rewards shares commit.")]
+    QuarryNotSynced,
+    #[msg("Not all quarries have been synchronized with the latest rewards shares commit.")]
+    RewardsSharesNotSynced,
+    #[msg("No rewards shares are staged.")]
+    NoStagedRewardsShares,
+    #[msg("Rewards shares are staged or pending synchronization.")]
+    RewardsSharesStaged,
+    #[msg("Quarry requires unbonding before withdrawal.")]
+    UnbondingRequired,
+    #[msg("Unbonding period has not elapsed.")]
+    UnbondingNotComplete,
+    #[msg("Miner has no unbonding balance.")]
+    NoUnbondingBalance,
+    #[msg("Invalid unbonding period.")]
+    InvalidUnbondingPeriod,
+    #[msg("Invalid early exit penalty.")]
+    InvalidEarlyExitPenalty,
+    #[msg("Destination miner is not empty.")]
+    DestinationMinerNotEmpty,
+    #[msg("Miner is not empty.")]
+    MinerNotEmpty,
+    #[msg("Quarry is not empty.")]
+    QuarryNotEmpty,
+    #[msg("Claim fee exceeds the maximum claim fee.")]
+    ClaimFeeExceedsMax,
+    #[msg("Invalid referral fee.")]
+    InvalidReferralFee,
+    #[msg("Vault-funded rewarders require a nonzero annual rewards rate.")]
+    InvalidAnnualRewardsRate,
+    #[msg("Rewards vault still funds rewards.")]
+    RewardsVaultNotExhausted,
+    #[msg("Miner authority is not in the quarry's allowlist.")]
+    MinerNotAllowlisted,
+    #[msg("Quarry deposit cap exceeded.")]
+    QuarryDepositCapExceeded,
+    #[msg("Miner deposit cap exceeded.")]
+    MinerDepositCapExceeded,
+    #[msg("This action is paused.")]
+    ScopePaused,
+    #[msg("Invalid pause scopes.")]
+    InvalidPausedScopes,
+    #[msg("Start timestamp must not be in the past.")]
+    InvalidStartTs,
+    #[msg("Miner checkpoints must be supplied as a writable remaining account.")]
+    MissingMinerCheckpoints,
+    #[msg("No balance checkpoint is retained for the timestamp.")]
+    BalanceCheckpointNotFound,
+    #[msg("Emergency withdrawals are only allowed while withdrawals are paused.")]
+    WithdrawalsNotPaused,
+    #[msg("Tokens subject to an early exit penalty may not be migrated.")]
+    EarlyExitPenaltyApplies,
+    #[msg("Emission schedule may not change the multiplier before the current time.")]
+    EmissionScheduleChangesPast,
+    #[msg("The referrer may not be the miner authority.")]
+    InvalidReferrer,
+    #[msg("Not all quarries have been synchronized with the latest rewards epoch.")]
+    RewardsEpochNotSynced,
 }
@@ -24,3 +24,16 @@ macro_rules! gen_miner_signer_seeds {
         ]
     };
 }
+
+/// Generates the signer seeds for a [crate::Quarry].
+#[macro_export]
+macro_rules! gen_quarry_signer_seeds {
+    ($quarry:expr) => {
+        &[
+            b"Quarry".as_ref(),
+            $quarry.rewarder.as_ref(),
+            $quarry.token_mint_key.as_ref(),
+            &[$quarry.bump],
+        ]
+    };
+}
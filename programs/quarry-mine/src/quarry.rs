@@ -3,35 +3,253 @@
 use anchor_lang::prelude::*;
 use vipers::prelude::*;
 
-use crate::{payroll::Payroll, Miner, Quarry, Rewarder};
+use crate::{
+    allowlist::verify_allowlist_proof,
+    payroll::{
+        calculate_rewards_earned_at_unsafe, divide_rewards_per_token_unsafe, Payroll,
+        REWARDS_DENOMINATOR,
+    },
+    Miner, Quarry, RewardStream, Rewarder, RewardsSnapshot, MAX_BPS, MAX_REWARDS_SNAPSHOTS,
+    REWARDS_SNAPSHOT_INTERVAL,
+};
 use num_traits::cast::ToPrimitive;
+use spl_math::uint::U192;
+use std::cmp;
 
 /// An action for a user to take on the staking pool.
 pub enum StakeAction {
     /// Stake into a [Quarry].
     Stake,
+    /// Stake into a [Quarry] on behalf of the [Miner]'s authority,
+    /// without restarting its early exit period.
+    StakeFor,
+    /// Stake tokens migrated from another [Quarry] which were staked by the authority at
+    /// `stake_ts`, so that migrating does not restart the early exit period.
+    Migrate {
+        /// The [Miner::last_stake_ts] of the source [Miner].
+        stake_ts: i64,
+    },
     /// Withdraw from the [Quarry].
     Withdraw,
 }
 
 impl Quarry {
     /// Updates the quarry by synchronizing its rewards rate with the rewarder.
+    ///
+    /// If the rewarder committed new rewards shares or started a new rewards epoch since the
+    /// quarry was last updated, the quarry accrues at its previous rate up to the commit or epoch.
     pub fn update_rewards_internal(
         &mut self,
         current_ts: i64,
         rewarder: &Rewarder,
         payroll: &Payroll,
     ) -> Result<()> {
-        let updated_rewards_per_token_stored = payroll.calculate_reward_per_token(current_ts)?;
+        let committed_payroll;
+        let payroll = if self.is_behind_rewards_shares_commit(rewarder) {
+            let commit_ts = rewarder.last_rewards_shares_commit_ts;
+            invariant!(current_ts >= commit_ts, InvalidTimestamp);
+            if commit_ts > payroll.last_checkpoint_ts {
+                self.accrue_rewards(payroll, commit_ts)?;
+            }
+            if self.has_pending_rewards_share {
+                self.rewards_share = self.pending_rewards_share;
+                self.pending_rewards_share = 0;
+                self.has_pending_rewards_share = false;
+            }
+            self.annual_rewards_rate =
+                rewarder.compute_quarry_annual_rewards_rate(self.rewards_share)?;
+            committed_payroll = self.payroll(rewarder);
+            &committed_payroll
+        } else {
+            payroll
+        };
+
+        let epoch_payroll;
+        let payroll = if self.is_behind_rewards_epoch(rewarder) {
+            let epoch_ts = rewarder.rewards_epoch_ts;
+            invariant!(current_ts >= epoch_ts, InvalidTimestamp);
+            if epoch_ts > payroll.last_checkpoint_ts {
+                // the previous funding may have run out before the epoch
+                let previous_payroll = self.payroll(rewarder).with_famine_ts(cmp::max(
+                    rewarder.previous_funded_until_ts,
+                    self.last_update_ts,
+                ));
+                self.accrue_rewards(&previous_payroll, epoch_ts)?;
+            }
+            // nothing is paid for the time the vault was empty before the epoch
+            self.last_update_ts = cmp::max(self.last_update_ts, epoch_ts);
+            self.annual_rewards_rate =
+                rewarder.compute_quarry_annual_rewards_rate(self.rewards_share)?;
+            epoch_payroll = self.payroll(rewarder);
+            &epoch_payroll
+        } else {
+            payroll
+        };
+
         // Update quarry struct
-        self.rewards_per_token_stored = updated_rewards_per_token_stored;
+        self.accrue_rewards(payroll, current_ts)?;
         self.annual_rewards_rate =
             rewarder.compute_quarry_annual_rewards_rate(self.rewards_share)?;
+
+        Ok(())
+    }
+
+    /// Checkpoints the rewards issued by the [Payroll] up to `current_ts`.
+    fn accrue_rewards(&mut self, payroll: &Payroll, current_ts: i64) -> Result<()> {
+        self.record_rewards_snapshots(payroll, current_ts)?;
+        let (rewards_issued, rewards_issued_remainder) =
+            payroll.calculate_rewards_issued(current_ts, self.rewards_issued_remainder)?;
+        self.total_rewards_accrued =
+            unwrap_int!(self.total_rewards_accrued.checked_add(rewards_issued));
+        self.rewards_issued_remainder = rewards_issued_remainder;
+        let (rewards_per_token_stored, rewards_per_token_remainder) =
+            payroll.calculate_reward_per_token_and_remainder(current_ts)?;
+        self.rewards_per_token_stored = rewards_per_token_stored;
+        self.rewards_per_token_remainder = rewards_per_token_remainder;
         self.last_update_ts = payroll.last_time_reward_applicable(current_ts);
+        Ok(())
+    }
+
+    /// Takes a [RewardsSnapshot] for each interval of [Quarry::rewards_snapshots]
+    /// which ended between the last checkpoint and `current_ts`.
+    fn record_rewards_snapshots(&mut self, payroll: &Payroll, current_ts: i64) -> Result<()> {
+        let total_weighted_tokens = self.total_weighted_tokens();
+        for index in 0..MAX_REWARDS_SNAPSHOTS {
+            let interval = REWARDS_SNAPSHOT_INTERVAL << index;
+            let snapshot_ts = unwrap_int!(current_ts.checked_sub(current_ts.rem_euclid(interval)));
+            if snapshot_ts < payroll.last_checkpoint_ts
+                || snapshot_ts <= self.rewards_snapshots[index].timestamp
+            {
+                continue;
+            }
+
+            let mut snapshot = RewardsSnapshot {
+                timestamp: snapshot_ts,
+                rewards_per_token_stored: payroll.calculate_reward_per_token(snapshot_ts)?,
+                ..Default::default()
+            };
+            for (stream, rewards_per_token) in self
+                .reward_streams
+                .iter()
+                .zip(snapshot.stream_rewards_per_token_stored.iter_mut())
+            {
+                // a stream already updated past the snapshot is left at zero,
+                // which pays no boost rather than too much
+                if stream.is_active() && stream.last_update_ts <= snapshot_ts {
+                    *rewards_per_token = stream
+                        .payroll(total_weighted_tokens)
+                        .with_start_ts(self.start_ts)
+                        .calculate_reward_per_token(snapshot_ts)?;
+                }
+            }
+            self.rewards_snapshots[index] = snapshot;
+        }
+        Ok(())
+    }
+
+    /// Returns the latest [RewardsSnapshot] taken at or before `timestamp`.
+    fn rewards_snapshot_at(&self, timestamp: i64) -> RewardsSnapshot {
+        self.rewards_snapshots
+            .iter()
+            .filter(|snapshot| snapshot.timestamp <= timestamp)
+            .max_by_key(|snapshot| snapshot.timestamp)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns true if the rewarder committed rewards shares which the quarry has not applied.
+    fn is_behind_rewards_shares_commit(&self, rewarder: &Rewarder) -> bool {
+        self.rewards_shares_generation < rewarder.rewards_shares_generation
+            && (self.has_pending_rewards_share
+                || self.last_update_ts < rewarder.last_rewards_shares_commit_ts)
+    }
+
+    /// Records that the quarry is synchronized with the latest rewards shares commit.
+    /// This must be called after [Quarry::update_rewards_internal].
+    pub fn sync_rewards_shares_generation(&mut self, rewarder: &mut Rewarder) -> Result<()> {
+        if self.rewards_shares_generation != rewarder.rewards_shares_generation {
+            self.rewards_shares_generation = rewarder.rewards_shares_generation;
+            rewarder.num_quarries_pending_sync =
+                unwrap_int!(rewarder.num_quarries_pending_sync.checked_sub(1));
+        }
+        Ok(())
+    }
+
+    /// Returns true if the rewarder started a rewards epoch which the quarry has not applied.
+    fn is_behind_rewards_epoch(&self, rewarder: &Rewarder) -> bool {
+        self.rewards_epoch < rewarder.rewards_epoch
+            && self.last_update_ts <= rewarder.rewards_epoch_ts
+    }
+
+    /// Records that the quarry is synchronized with the latest rewards epoch.
+    /// This must be called after [Quarry::update_rewards_internal].
+    pub fn sync_rewards_epoch(&mut self, rewarder: &mut Rewarder) -> Result<()> {
+        if self.rewards_epoch != rewarder.rewards_epoch {
+            self.rewards_epoch = rewarder.rewards_epoch;
+            rewarder.num_quarries_pending_epoch =
+                unwrap_int!(rewarder.num_quarries_pending_epoch.checked_sub(1));
+        }
+        Ok(())
+    }
+
+    /// Creates a [Payroll] for the quarry's rewards from the [Rewarder].
+    ///
+    /// Rewards of a vault-funded [Rewarder] end once its vault runs out.
+    pub fn payroll(&self, rewarder: &Rewarder) -> Payroll {
+        let payroll = Payroll::from(*self).with_emission_schedule(rewarder.emission_schedule);
+        if rewarder.is_vault_funded() {
+            // never move the checkpoint back before a famine that has already passed
+            payroll.with_famine_ts(cmp::max(rewarder.funded_until_ts, self.last_update_ts))
+        } else {
+            payroll
+        }
+    }
+
+    /// Takes the rewards earned by a [Miner] so that they may be claimed.
+    pub fn claim_rewards_earned(&mut self, miner: &mut Miner) -> Result<u64> {
+        let amount_claimable = miner.rewards_earned;
+        miner.rewards_earned = 0;
+        self.total_rewards_claimed =
+            unwrap_int!(self.total_rewards_claimed.checked_add(amount_claimable));
+        Ok(amount_claimable)
+    }
+
+    /// Records the fees charged on rewards claimed from the quarry.
+    pub fn record_claim_fees(&mut self, fees: u64) -> Result<()> {
+        self.total_claim_fees = unwrap_int!(self.total_claim_fees.checked_add(fees));
+        Ok(())
+    }
 
+    /// Adds the changes in the quarry's reward totals since they were last reported
+    /// to the totals of the [Rewarder].
+    pub fn report_rewards_totals(&mut self, rewarder: &mut Rewarder) -> Result<()> {
+        rewarder.total_rewards_accrued = unwrap_int!(rewarder
+            .total_rewards_accrued
+            .checked_sub(self.rewards_accrued_reported)
+            .and_then(|v| v.checked_add(self.total_rewards_accrued)));
+        rewarder.total_rewards_claimed = unwrap_int!(rewarder
+            .total_rewards_claimed
+            .checked_sub(self.rewards_claimed_reported)
+            .and_then(|v| v.checked_add(self.total_rewards_claimed)));
+        rewarder.total_claim_fees = unwrap_int!(rewarder
+            .total_claim_fees
+            .checked_sub(self.claim_fees_reported)
+            .and_then(|v| v.checked_add(self.total_claim_fees)));
+
+        self.rewards_accrued_reported = self.total_rewards_accrued;
+        self.rewards_claimed_reported = self.total_rewards_claimed;
+        self.claim_fees_reported = self.total_claim_fees;
         Ok(())
     }
 
+    /// Rewards accrued to the quarry's miners which have not yet been claimed or forfeited.
+    pub fn rewards_outstanding(&self) -> Result<u64> {
+        Ok(unwrap_int!(self
+            .total_rewards_accrued
+            .checked_sub(self.total_rewards_claimed)
+            .and_then(|v| v.checked_sub(self.total_rewards_forfeited))))
+    }
+
     /// Updates the quarry and miner with the latest info.
     /// <https://github.com/Synthetixio/synthetix/blob/aeee6b2c82588681e1f99202663346098d1866ac/contracts/StakingRewards.sol#L158>
     pub fn update_rewards_and_miner(
@@ -40,13 +258,19 @@ impl Quarry {
         rewarder: &Rewarder,
         current_ts: i64,
     ) -> Result<()> {
-        let payroll: Payroll = (*self).into();
+        if miner.lock_boost_bps != 0 && !miner.is_locked(current_ts) {
+            self.settle_expired_lock(miner, rewarder)?;
+        }
+
+        let payroll = self.payroll(rewarder);
         self.update_rewards_internal(current_ts, rewarder, &payroll)?;
 
+        // Rewards have accrued up to the current time.
+        let payroll = self.payroll(rewarder);
         let updated_rewards_earned = unwrap_int!(payroll
             .calculate_rewards_earned(
                 current_ts,
-                miner.balance,
+                miner.weighted_balance(),
                 miner.rewards_per_token_paid,
                 miner.rewards_earned,
             )?
@@ -57,6 +281,150 @@ impl Quarry {
         miner.rewards_earned = updated_rewards_earned;
         miner.rewards_per_token_paid = self.rewards_per_token_stored;
 
+        self.update_reward_streams_and_miner(miner, current_ts)?;
+        self.update_miner_penalties(miner)?;
+
+        Ok(())
+    }
+
+    /// Credits a [Miner] with its share of the early exit penalties redistributed
+    /// in the staked token since it was last updated.
+    fn update_miner_penalties(&self, miner: &mut Miner) -> Result<()> {
+        miner.penalties_earned = unwrap_int!(calculate_rewards_earned_at_unsafe(
+            self.penalties_per_token_stored,
+            miner.balance,
+            miner.penalties_per_token_paid,
+            miner.penalties_earned,
+        )
+        .and_then(|v| v.to_u64()));
+        miner.penalties_per_token_paid = self.penalties_per_token_stored;
+        Ok(())
+    }
+
+    /// Settles a [Miner] at its boosted weight up to the end of its expired lock,
+    /// then removes the boost so that the rest of its rewards accrue at its balance.
+    ///
+    /// Rewards which the quarry or a [RewardStream] accrued past the end of the lock
+    /// can no longer be split at it, so the boost is only paid up to the latest
+    /// [RewardsSnapshot] taken before the end of the lock.
+    fn settle_expired_lock(&mut self, miner: &mut Miner, rewarder: &Rewarder) -> Result<()> {
+        let lock_end_ts = miner.lock_end_ts;
+        let snapshot = self.rewards_snapshot_at(lock_end_ts);
+        let weighted_balance = miner.weighted_balance();
+
+        let rewards_per_token = if self.last_update_ts <= lock_end_ts {
+            let payroll = self.payroll(rewarder);
+            if self.is_behind_rewards_shares_commit(rewarder)
+                && rewarder.last_rewards_shares_commit_ts > lock_end_ts
+            {
+                // the lock ended before the commit, so the previous rate applies
+                self.accrue_rewards(&payroll, lock_end_ts)?;
+            } else {
+                self.update_rewards_internal(lock_end_ts, rewarder, &payroll)?;
+            }
+            self.rewards_per_token_stored
+        } else {
+            snapshot.rewards_per_token_stored
+        };
+        let rewards_per_token = cmp::max(rewards_per_token, miner.rewards_per_token_paid);
+        miner.rewards_earned = unwrap_int!(calculate_rewards_earned_at_unsafe(
+            rewards_per_token,
+            weighted_balance,
+            miner.rewards_per_token_paid,
+            miner.rewards_earned,
+        )
+        .and_then(|v| v.to_u64()));
+        miner.rewards_per_token_paid = rewards_per_token;
+
+        let total_weighted_tokens = self.total_weighted_tokens();
+        for ((stream, miner_stream), snapshot_rewards_per_token) in self
+            .reward_streams
+            .iter_mut()
+            .zip(miner.reward_streams.iter_mut())
+            .zip(snapshot.stream_rewards_per_token_stored)
+        {
+            if !stream.is_active() {
+                continue;
+            }
+            let rewards_per_token = if stream.last_update_ts <= lock_end_ts {
+                let payroll = stream
+                    .payroll(total_weighted_tokens)
+                    .with_start_ts(self.start_ts);
+                stream.update_rewards_internal(lock_end_ts, &payroll)?;
+                stream.rewards_per_token_stored
+            } else {
+                snapshot_rewards_per_token
+            };
+            let rewards_per_token =
+                cmp::max(rewards_per_token, miner_stream.rewards_per_token_paid);
+            miner_stream.rewards_earned = unwrap_int!(calculate_rewards_earned_at_unsafe(
+                rewards_per_token,
+                weighted_balance,
+                miner_stream.rewards_per_token_paid,
+                miner_stream.rewards_earned,
+            )
+            .and_then(|v| v.to_u64()));
+            miner_stream.rewards_per_token_paid = rewards_per_token;
+        }
+
+        miner.lock_boost_bps = 0;
+        self.update_boosted_balance(miner)
+    }
+
+    /// Updates all active [RewardStream]s of the quarry and the miner's checkpoints of them.
+    fn update_reward_streams_and_miner(
+        &mut self,
+        miner: &mut Miner,
+        current_ts: i64,
+    ) -> Result<()> {
+        let total_weighted_tokens = self.total_weighted_tokens();
+        let weighted_balance = miner.weighted_balance();
+        for (stream, miner_stream) in self
+            .reward_streams
+            .iter_mut()
+            .zip(miner.reward_streams.iter_mut())
+        {
+            if !stream.is_active() {
+                continue;
+            }
+            let payroll = stream
+                .payroll(total_weighted_tokens)
+                .with_start_ts(self.start_ts);
+            stream.update_rewards_internal(current_ts, &payroll)?;
+
+            miner_stream.rewards_earned = unwrap_int!(payroll
+                .calculate_rewards_earned(
+                    current_ts,
+                    weighted_balance,
+                    miner_stream.rewards_per_token_paid,
+                    miner_stream.rewards_earned,
+                )?
+                .to_u64());
+            miner_stream.rewards_per_token_paid = stream.rewards_per_token_stored;
+        }
+        Ok(())
+    }
+
+    /// Returns true if only allowlisted authorities may stake into the quarry.
+    pub fn is_gated(&self) -> bool {
+        self.allowlist_root != [0; 32]
+    }
+
+    /// Returns true if the [Miner] may stake into the quarry.
+    pub fn is_allowlisted(&self, miner: &Miner) -> bool {
+        !self.is_gated() || miner.allowlist_root == self.allowlist_root
+    }
+
+    /// Approves a [Miner] against the current [Quarry::allowlist_root].
+    /// The proof is only checked if the quarry is gated.
+    pub fn approve_miner(&self, miner: &mut Miner, proof: &[[u8; 32]]) -> Result<()> {
+        if self.is_gated() {
+            invariant!(
+                verify_allowlist_proof(proof, self.allowlist_root, &miner.authority),
+                MinerNotAllowlisted
+            );
+        }
+        miner.allowlist_root = self.allowlist_root;
         Ok(())
     }
 
@@ -71,18 +439,390 @@ impl Quarry {
     ) -> Result<()> {
         self.update_rewards_and_miner(miner, rewarder, current_ts)?;
         match action {
-            StakeAction::Stake => {
-                miner.balance = unwrap_int!(miner.balance.checked_add(amount));
-                self.total_tokens_deposited =
+            StakeAction::Stake | StakeAction::StakeFor | StakeAction::Migrate { .. } => {
+                invariant!(self.is_allowlisted(miner), MinerNotAllowlisted);
+                let balance = unwrap_int!(miner.balance.checked_add(amount));
+                let total_tokens_deposited =
                     unwrap_int!(self.total_tokens_deposited.checked_add(amount));
+                invariant!(
+                    self.max_miner_balance == 0 || balance <= self.max_miner_balance,
+                    MinerDepositCapExceeded
+                );
+                invariant!(
+                    self.max_total_tokens_deposited == 0
+                        || total_tokens_deposited <= self.max_total_tokens_deposited,
+                    QuarryDepositCapExceeded
+                );
+                match action {
+                    StakeAction::Stake => {
+                        miner.last_stake_ts = current_ts;
+                    }
+                    StakeAction::Migrate { stake_ts } => {
+                        miner.last_stake_ts = cmp::max(miner.last_stake_ts, stake_ts);
+                    }
+                    _ => {
+                        if self.is_early_exit_period_over(miner.last_staked_for_ts, current_ts)? {
+                            miner.staked_for_balance = 0;
+                        }
+                        miner.staked_for_balance =
+                            unwrap_int!(miner.staked_for_balance.checked_add(amount));
+                        miner.last_staked_for_ts = current_ts;
+                    }
+                }
+                miner.balance = balance;
+                self.total_tokens_deposited = total_tokens_deposited;
             }
             StakeAction::Withdraw => {
+                invariant!(!miner.is_locked(current_ts), MinerLocked);
                 miner.balance = unwrap_int!(miner.balance.checked_sub(amount));
+                // the authority's own tokens are withdrawn first
+                miner.staked_for_balance = miner.staked_for_balance.min(miner.balance);
                 self.total_tokens_deposited =
                     unwrap_int!(self.total_tokens_deposited.checked_sub(amount));
             }
         }
+        self.update_boosted_balance(miner)?;
+
+        Ok(())
+    }
+
+    /// Moves `amount` of a [Miner]'s balance into its [Miner::unbonding_balance].
+    ///
+    /// The unbonding tokens stop earning rewards immediately. Requesting another unbond
+    /// restarts the [Quarry::unbonding_period] for the whole unbonding balance.
+    pub fn process_unbond_request_internal(
+        &mut self,
+        current_ts: i64,
+        rewarder: &Rewarder,
+        miner: &mut Miner,
+        amount: u64,
+    ) -> Result<()> {
+        let penalty = self.compute_early_exit_penalty(miner, current_ts, amount)?;
+        self.process_stake_action_internal(
+            StakeAction::Withdraw,
+            current_ts,
+            rewarder,
+            miner,
+            amount,
+        )?;
+        miner.unbonding_penalty = unwrap_int!(miner.unbonding_penalty.checked_add(penalty));
+        miner.unbonding_balance = unwrap_int!(miner.unbonding_balance.checked_add(amount));
+        miner.unbonding_end_ts = unwrap_int!(current_ts.checked_add(self.unbonding_period));
+        Ok(())
+    }
+
+    /// Returns true if early exit penalties are redistributed to the [Quarry]'s miners
+    /// rather than sent to the [Quarry::early_exit_treasury].
+    pub fn redistributes_early_exit_penalty(&self) -> bool {
+        self.early_exit_treasury == Pubkey::default()
+    }
+
+    /// Returns true if the early exit period of a stake made at `stake_ts` has elapsed.
+    fn is_early_exit_period_over(&self, stake_ts: i64, current_ts: i64) -> Result<bool> {
+        Ok(self.early_exit_period == 0
+            || current_ts >= unwrap_int!(stake_ts.checked_add(self.early_exit_period)))
+    }
+
+    /// Computes the penalty charged on withdrawing `amount` from a [Miner].
+    /// This must be computed before the withdrawal is processed.
+    ///
+    /// Within the early exit period of the authority's last stake, the whole amount is
+    /// penalized. Otherwise, only the part of the amount which exceeds the authority's own
+    /// tokens is penalized, within the early exit period of the last stake by someone else.
+    pub fn compute_early_exit_penalty(
+        &self,
+        miner: &Miner,
+        current_ts: i64,
+        amount: u64,
+    ) -> Result<u64> {
+        let penalized_amount =
+            if !self.is_early_exit_period_over(miner.last_stake_ts, current_ts)? {
+                amount
+            } else if !self.is_early_exit_period_over(miner.last_staked_for_ts, current_ts)? {
+                let own_balance = unwrap_int!(miner.balance.checked_sub(miner.staked_for_balance));
+                amount.saturating_sub(own_balance)
+            } else {
+                0
+            };
+        Ok(unwrap_int!(::u128::mul_div_u64(
+            penalized_amount,
+            self.early_exit_penalty_bps,
+            MAX_BPS
+        )))
+    }
+
+    /// Distributes a penalty to the [Quarry]'s miners as rewards, pro rata to their weight.
+    ///
+    /// Returns false if there is no one left to distribute the penalty to,
+    /// in which case it should not be charged.
+    pub fn redistribute_early_exit_penalty(&mut self, penalty: u64) -> Result<bool> {
+        let total_weighted_tokens = self.total_weighted_tokens();
+        if total_weighted_tokens == 0 {
+            return Ok(false);
+        }
+        let (rewards_per_token, remainder) = unwrap_int!(U192::from(penalty)
+            .checked_mul(REWARDS_DENOMINATOR.into())
+            .and_then(|rewards| divide_rewards_per_token_unsafe(
+                rewards,
+                self.rewards_per_token_remainder,
+                total_weighted_tokens
+            )));
+        self.rewards_per_token_stored =
+            unwrap_int!(self.rewards_per_token_stored.checked_add(rewards_per_token));
+        self.rewards_per_token_remainder = remainder;
+        self.total_rewards_accrued = unwrap_int!(self.total_rewards_accrued.checked_add(penalty));
+        Ok(true)
+    }
+
+    /// Returns true if early exit penalties are redistributed to the [Quarry]'s miners
+    /// in the staked token, since it is not the rewards token.
+    pub fn redistributes_early_exit_penalty_in_stake(&self, rewarder: &Rewarder) -> bool {
+        self.redistributes_early_exit_penalty()
+            && self.token_mint_key != rewarder.rewards_token_mint
+    }
+
+    /// Distributes a penalty to the [Quarry]'s miners in the staked token, pro rata to their
+    /// balance. The penalty must be held in the [Quarry::early_exit_penalty_vault].
+    ///
+    /// Returns false if there is no one left to distribute the penalty to,
+    /// in which case it should not be charged.
+    pub fn redistribute_early_exit_penalty_in_stake(&mut self, penalty: u64) -> Result<bool> {
+        if self.total_tokens_deposited == 0 {
+            return Ok(false);
+        }
+        let (penalties_per_token, remainder) = unwrap_int!(U192::from(penalty)
+            .checked_mul(REWARDS_DENOMINATOR.into())
+            .and_then(|penalty| divide_rewards_per_token_unsafe(
+                penalty,
+                self.penalties_per_token_remainder,
+                self.total_tokens_deposited
+            )));
+        self.penalties_per_token_stored = unwrap_int!(self
+            .penalties_per_token_stored
+            .checked_add(penalties_per_token));
+        self.penalties_per_token_remainder = remainder;
+        Ok(true)
+    }
+
+    /// Moves the entire position of a [Miner] to another [Miner] of the same [Quarry].
+    ///
+    /// Both miners are settled first. The destination must not have a balance, unbonding
+    /// balance or active lock, so the source's lock and unbonding state carry over unchanged.
+    pub fn process_transfer_miner_internal(
+        &mut self,
+        current_ts: i64,
+        rewarder: &Rewarder,
+        from: &mut Miner,
+        to: &mut Miner,
+    ) -> Result<()> {
+        self.update_rewards_and_miner(from, rewarder, current_ts)?;
+        self.update_rewards_and_miner(to, rewarder, current_ts)?;
+        invariant!(
+            to.balance == 0 && to.unbonding_balance == 0 && !to.is_locked(current_ts),
+            DestinationMinerNotEmpty
+        );
+        invariant!(self.is_allowlisted(to), MinerNotAllowlisted);
+
+        to.balance = from.balance;
+        to.boosted_balance = from.boosted_balance;
+        to.lock_end_ts = from.lock_end_ts;
+        to.lock_boost_bps = from.lock_boost_bps;
+        to.last_stake_ts = from.last_stake_ts;
+        to.staked_for_balance = from.staked_for_balance;
+        to.last_staked_for_ts = from.last_staked_for_ts;
+        to.unbonding_balance = from.unbonding_balance;
+        to.unbonding_end_ts = from.unbonding_end_ts;
+        to.unbonding_penalty = from.unbonding_penalty;
+        to.rewards_earned = unwrap_int!(to.rewards_earned.checked_add(from.rewards_earned));
+        for (to_stream, from_stream) in to
+            .reward_streams
+            .iter_mut()
+            .zip(from.reward_streams.iter_mut())
+        {
+            to_stream.rewards_earned = unwrap_int!(to_stream
+                .rewards_earned
+                .checked_add(from_stream.rewards_earned));
+            from_stream.rewards_earned = 0;
+        }
+        to.penalties_earned = unwrap_int!(to.penalties_earned.checked_add(from.penalties_earned));
+
+        from.balance = 0;
+        from.boosted_balance = 0;
+        from.lock_end_ts = 0;
+        from.lock_boost_bps = 0;
+        from.unbonding_balance = 0;
+        from.unbonding_end_ts = 0;
+        from.unbonding_penalty = 0;
+        from.rewards_earned = 0;
+        from.penalties_earned = 0;
+        from.staked_for_balance = 0;
+
+        Ok(())
+    }
+
+    /// Removes the entire position of a [Miner] without paying its rewards,
+    /// for use while withdrawals are paused.
+    ///
+    /// The [Quarry] is checkpointed first, if possible, so that the rewards of the remaining
+    /// miners up to now are not affected. The [Miner] itself is not updated, so its lock,
+    /// reward streams and rewards cannot prevent the withdrawal. Its lock, unbonding state
+    /// and unclaimed rewards are then forfeited.
+    /// Returns the rewards forfeited.
+    pub fn process_emergency_withdraw_internal(
+        &mut self,
+        current_ts: i64,
+        rewarder: &Rewarder,
+        miner: &mut Miner,
+    ) -> Result<u64> {
+        // the withdrawal must not fail if the quarry cannot be checkpointed
+        let mut checkpointed = *self;
+        if checkpointed
+            .checkpoint_rewards(rewarder, current_ts)
+            .is_ok()
+        {
+            *self = checkpointed;
+        }
+        let forfeited = self
+            .compute_rewards_forfeited(miner, rewarder, current_ts)
+            .unwrap_or(miner.rewards_earned);
+        self.total_rewards_forfeited =
+            unwrap_int!(self.total_rewards_forfeited.checked_add(forfeited));
+
+        self.total_tokens_deposited =
+            unwrap_int!(self.total_tokens_deposited.checked_sub(miner.balance));
+        self.total_boosted_tokens =
+            unwrap_int!(self.total_boosted_tokens.checked_sub(miner.boosted_balance));
+
+        miner.balance = 0;
+        miner.boosted_balance = 0;
+        miner.lock_end_ts = 0;
+        miner.lock_boost_bps = 0;
+        miner.unbonding_balance = 0;
+        miner.unbonding_end_ts = 0;
+        miner.unbonding_penalty = 0;
+        miner.staked_for_balance = 0;
+        miner.rewards_earned = 0;
+        for miner_stream in miner.reward_streams.iter_mut() {
+            miner_stream.rewards_earned = 0;
+        }
+
+        Ok(forfeited)
+    }
+
+    /// Checkpoints the rewards and reward streams of the quarry without updating any [Miner].
+    fn checkpoint_rewards(&mut self, rewarder: &Rewarder, current_ts: i64) -> Result<()> {
+        let payroll = self.payroll(rewarder);
+        self.update_rewards_internal(current_ts, rewarder, &payroll)?;
+
+        let total_weighted_tokens = self.total_weighted_tokens();
+        for stream in self.reward_streams.iter_mut() {
+            if !stream.is_active() {
+                continue;
+            }
+            let payroll = stream
+                .payroll(total_weighted_tokens)
+                .with_start_ts(self.start_ts);
+            stream.update_rewards_internal(current_ts, &payroll)?;
+        }
+        Ok(())
+    }
+
+    /// Computes the rewards a [Miner] has earned up to now without updating it.
+    fn compute_rewards_forfeited(
+        &self,
+        miner: &Miner,
+        rewarder: &Rewarder,
+        current_ts: i64,
+    ) -> Result<u64> {
+        // an expired boost is not counted, so that the forfeit is never overstated
+        let balance = if miner.is_locked(current_ts) {
+            miner.weighted_balance()
+        } else {
+            miner.balance
+        };
+        let payroll = self.payroll(rewarder);
+        Ok(unwrap_int!(payroll
+            .calculate_rewards_earned(
+                current_ts,
+                balance,
+                miner.rewards_per_token_paid,
+                miner.rewards_earned,
+            )?
+            .to_u64()))
+    }
+
+    /// Computes the boost of a lock lasting `lock_duration` seconds, in BPS.
+    pub fn compute_lock_boost_bps(&self, lock_duration: i64) -> Result<u64> {
+        invariant!(
+            lock_duration > 0 && lock_duration <= self.max_lock_duration,
+            InvalidLockDuration
+        );
+        Ok(unwrap_int!(::u128::mul_div_u64(
+            self.max_lock_boost_bps,
+            unwrap_int!(lock_duration.to_u64()),
+            unwrap_int!(self.max_lock_duration.to_u64()),
+        )))
+    }
+
+    /// Locks the balance of a [Miner] for `lock_duration` seconds.
+    ///
+    /// A lock may be extended but never shortened. The boost is recomputed
+    /// from the new duration and applies to the [Miner]'s entire balance.
+    pub fn process_lock_internal(
+        &mut self,
+        current_ts: i64,
+        rewarder: &Rewarder,
+        miner: &mut Miner,
+        lock_duration: i64,
+    ) -> Result<()> {
+        self.update_rewards_and_miner(miner, rewarder, current_ts)?;
+
+        let lock_boost_bps = self.compute_lock_boost_bps(lock_duration)?;
+        let lock_end_ts = unwrap_int!(current_ts.checked_add(lock_duration));
+        invariant!(lock_end_ts >= miner.lock_end_ts, InvalidLockDuration);
+
+        miner.lock_end_ts = lock_end_ts;
+        miner.lock_boost_bps = lock_boost_bps;
+        self.update_boosted_balance(miner)
+    }
+
+    /// Recomputes the [Miner::boosted_balance] after its balance or boost changed.
+    fn update_boosted_balance(&mut self, miner: &mut Miner) -> Result<()> {
+        let boosted_balance = unwrap_int!(::u128::mul_div_u64(
+            miner.balance,
+            miner.lock_boost_bps,
+            MAX_BPS
+        ));
+        self.total_boosted_tokens = unwrap_int!(self
+            .total_boosted_tokens
+            .checked_sub(miner.boosted_balance)
+            .and_then(|v| v.checked_add(boosted_balance)));
+        miner.boosted_balance = boosted_balance;
 
+        // the weighted total must fit in a u64
+        unwrap_int!(self
+            .total_tokens_deposited
+            .checked_add(self.total_boosted_tokens));
+        Ok(())
+    }
+}
+
+impl RewardStream {
+    /// Creates a [Payroll] for the stream.
+    pub fn payroll(&self, total_tokens_deposited: u64) -> Payroll {
+        Payroll::new(
+            self.famine_ts,
+            self.last_update_ts,
+            self.annual_rewards_rate,
+            self.rewards_per_token_stored,
+            total_tokens_deposited,
+        )
+    }
+
+    /// Checkpoints the stream's rewards per token.
+    pub fn update_rewards_internal(&mut self, current_ts: i64, payroll: &Payroll) -> Result<()> {
+        self.rewards_per_token_stored = payroll.calculate_reward_per_token(current_ts)?;
+        self.last_update_ts = payroll.last_time_reward_applicable(current_ts);
         Ok(())
     }
 }
@@ -91,7 +831,7 @@ impl Quarry {
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
-    use crate::{payroll::PRECISION_MULTIPLIER, quarry::StakeAction};
+    use crate::{payroll::PRECISION_MULTIPLIER, quarry::StakeAction, MinerRewardStream, MAX_BPS};
 
     const SECONDS_PER_DAY: u64 = 86_400;
     const DEFAULT_TOKEN_DECIMALS: u8 = 6;
@@ -110,10 +850,7 @@ mod tests {
         quarry
             .update_rewards_and_miner(miner, rewarder, current_ts)
             .unwrap();
-        let amount_claimable = miner.rewards_earned;
-        miner.rewards_earned = 0;
-
-        amount_claimable
+        quarry.claim_rewards_earned(miner).unwrap()
     }
 
     fn sim_stake(
@@ -382,4 +1119,1554 @@ mod tests {
             expected_miner_two_rewards_earned
         );
     }
+
+    #[test]
+    fn test_reward_stream_two_miners() {
+        let quarry = &mut Quarry::default();
+        quarry.famine_ts = i64::MAX;
+        let miner_vault_one = &mut MinerVault { balance: 0 };
+        let miner_vault_two = &mut MinerVault { balance: 0 };
+
+        // the rewarder itself emits nothing
+        let rewarder = Rewarder {
+            bump: 254,
+            num_quarries: 1,
+            ..Default::default()
+        };
+
+        let daily_stream_rate = to_unit(1_000);
+        quarry.reward_streams[1] = RewardStream {
+            mint_wrapper: Pubkey::new_unique(),
+            rewards_token_mint: Pubkey::new_unique(),
+            annual_rewards_rate: daily_stream_rate * 365,
+            famine_ts: i64::MAX,
+            ..Default::default()
+        };
+
+        let miner_one = &mut Miner::default();
+        let miner_two = &mut Miner::default();
+
+        let mut current_ts: i64 = 0;
+        let total_to_stake = to_unit(500);
+        sim_stake(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_one,
+            miner_one,
+            total_to_stake,
+        );
+
+        // Fastforward time by 2 days, then miner two joins
+        current_ts += SECONDS_PER_DAY as i64 * 2;
+        sim_stake(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_two,
+            miner_two,
+            total_to_stake,
+        );
+        assert_eq!(miner_two.reward_streams[1].rewards_earned, 0);
+        assert_eq!(
+            miner_two.reward_streams[1].rewards_per_token_paid,
+            quarry.reward_streams[1].rewards_per_token_stored
+        );
+
+        // Fastforward time by 2 days
+        current_ts += SECONDS_PER_DAY as i64 * 2;
+        assert_eq!(
+            sim_claim(current_ts, &rewarder, quarry, miner_vault_one, miner_one),
+            0
+        );
+        assert_eq!(
+            sim_claim(current_ts, &rewarder, quarry, miner_vault_two, miner_two),
+            0
+        );
+
+        assert_eq!(
+            miner_one.reward_streams[1].rewards_earned,
+            daily_stream_rate * 3
+        );
+        assert_eq!(
+            miner_two.reward_streams[1].rewards_earned,
+            daily_stream_rate
+        );
+        assert_eq!(miner_one.reward_streams[0], MinerRewardStream::default());
+        assert_eq!(miner_one.reward_streams[2], MinerRewardStream::default());
+
+        // famine stops the stream
+        quarry.reward_streams[1].famine_ts = current_ts;
+        current_ts += SECONDS_PER_DAY as i64 * 2;
+        sim_claim(current_ts, &rewarder, quarry, miner_vault_one, miner_one);
+        assert_eq!(
+            miner_one.reward_streams[1].rewards_earned,
+            daily_stream_rate * 3
+        );
+    }
+
+    #[test]
+    fn test_lock_boost() {
+        let quarry = &mut Quarry::default();
+        quarry.famine_ts = i64::MAX;
+        quarry.rewards_share = 100;
+        quarry.max_lock_duration = SECONDS_PER_DAY as i64 * 10;
+        quarry.max_lock_boost_bps = MAX_BPS * 2;
+        let miner_vault_one = &mut MinerVault { balance: 0 };
+        let miner_vault_two = &mut MinerVault { balance: 0 };
+
+        let daily_rewards_rate = to_unit(5_000);
+        let rewarder = Rewarder {
+            bump: 254,
+            annual_rewards_rate: daily_rewards_rate * 365,
+            num_quarries: 1,
+            total_rewards_shares: quarry.rewards_share,
+            ..Default::default()
+        };
+        let miner_one = &mut Miner::default();
+        let miner_two = &mut Miner::default();
+
+        let mut current_ts: i64 = 0;
+        let total_to_stake = to_unit(500);
+        sim_stake(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_one,
+            miner_one,
+            total_to_stake,
+        );
+        sim_stake(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_two,
+            miner_two,
+            total_to_stake,
+        );
+
+        // locks longer than the max are rejected
+        assert!(quarry
+            .process_lock_internal(
+                current_ts,
+                &rewarder,
+                miner_one,
+                quarry.max_lock_duration + 1
+            )
+            .is_err());
+
+        // Miner one locks for half of the max duration: 2x weight
+        quarry
+            .process_lock_internal(current_ts, &rewarder, miner_one, SECONDS_PER_DAY as i64 * 5)
+            .unwrap();
+        assert_eq!(miner_one.lock_boost_bps, MAX_BPS);
+        assert_eq!(miner_one.boosted_balance, total_to_stake);
+        assert_eq!(quarry.total_boosted_tokens, total_to_stake);
+        assert_eq!(quarry.total_weighted_tokens(), total_to_stake * 3);
+
+        // locks may not be shortened
+        assert!(quarry
+            .process_lock_internal(current_ts, &rewarder, miner_one, 1)
+            .is_err());
+
+        // Fastforward time by 3 days
+        current_ts += SECONDS_PER_DAY as i64 * 3;
+        assert!(quarry
+            .process_stake_action_internal(
+                StakeAction::Withdraw,
+                current_ts,
+                &rewarder,
+                miner_one,
+                1
+            )
+            .is_err());
+        let total_distributed = daily_rewards_rate * 3;
+        assert_eq!(
+            sim_claim(current_ts, &rewarder, quarry, miner_vault_one, miner_one),
+            total_distributed * 2 / 3
+        );
+        assert_eq!(
+            sim_claim(current_ts, &rewarder, quarry, miner_vault_two, miner_two),
+            total_distributed / 3
+        );
+
+        // Fastforward time past the end of the lock; the boost ends on the next checkpoint
+        current_ts += SECONDS_PER_DAY as i64 * 3;
+        sim_withdraw(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_one,
+            miner_one,
+            total_to_stake,
+        );
+        assert_eq!(miner_one.lock_boost_bps, 0);
+        assert_eq!(miner_one.boosted_balance, 0);
+        assert_eq!(quarry.total_boosted_tokens, 0);
+        assert_eq!(quarry.total_weighted_tokens(), total_to_stake);
+    }
+
+    #[test]
+    fn test_lock_boost_ends_with_lock() {
+        let day = SECONDS_PER_DAY as i64;
+        let daily_rewards_rate = to_unit(6_000);
+        let rewarder = Rewarder {
+            bump: 254,
+            annual_rewards_rate: daily_rewards_rate * 365,
+            num_quarries: 1,
+            total_rewards_shares: 100,
+            ..Default::default()
+        };
+        let total_to_stake = to_unit(500);
+        let setup = || {
+            let mut quarry = Quarry {
+                famine_ts: i64::MAX,
+                rewards_share: 100,
+                max_lock_duration: day * 10,
+                max_lock_boost_bps: MAX_BPS * 2,
+                ..Default::default()
+            };
+            let mut miner_one = Miner::default();
+            let mut miner_two = Miner::default();
+            let vault = &mut MinerVault { balance: 0 };
+            sim_stake(
+                0,
+                &rewarder,
+                &mut quarry,
+                vault,
+                &mut miner_one,
+                total_to_stake,
+            );
+            sim_stake(
+                0,
+                &rewarder,
+                &mut quarry,
+                vault,
+                &mut miner_two,
+                total_to_stake,
+            );
+            // 2x weight for 5 days
+            quarry
+                .process_lock_internal(0, &rewarder, &mut miner_one, day * 5)
+                .unwrap();
+            (quarry, miner_one, miner_two)
+        };
+        let vault = &mut MinerVault { balance: 0 };
+
+        // Nothing touches the quarry until long after the lock ended:
+        // the boost is paid up to the end of the lock only.
+        let (mut quarry, mut miner_one, mut miner_two) = setup();
+        let claimed_one = sim_claim(day * 30, &rewarder, &mut quarry, vault, &mut miner_one);
+        assert_eq!(miner_one.lock_boost_bps, 0);
+        assert_eq!(quarry.total_weighted_tokens(), total_to_stake * 2);
+        let claimed_two = sim_claim(day * 30, &rewarder, &mut quarry, vault, &mut miner_two);
+        let expected_one = daily_rewards_rate * 5 * 2 / 3 + daily_rewards_rate * 25 / 2;
+        let expected_two = daily_rewards_rate * 5 / 3 + daily_rewards_rate * 25 / 2;
+        assert!(expected_one - claimed_one <= 1);
+        assert!(expected_two - claimed_two <= 1);
+
+        // The quarry moved past the end of the lock before the miner was updated:
+        // the boost is paid up to the latest snapshot before the end of the lock,
+        // and never past it.
+        let (mut quarry, mut miner_one, mut miner_two) = setup();
+        for days in 1..=7 {
+            sim_claim(day * days, &rewarder, &mut quarry, vault, &mut miner_two);
+        }
+        assert_eq!(quarry.rewards_snapshot_at(day * 5).timestamp, day * 4);
+        let claimed_one = sim_claim(day * 7, &rewarder, &mut quarry, vault, &mut miner_one);
+        assert_eq!(miner_one.lock_boost_bps, 0);
+        let expected_one = daily_rewards_rate * 4 * 2 / 3 + daily_rewards_rate * 3 / 3;
+        assert!(expected_one - claimed_one <= 1);
+
+        // afterwards, both miners earn equally
+        let claimed_one = sim_claim(day * 8, &rewarder, &mut quarry, vault, &mut miner_one);
+        let claimed_two = sim_claim(day * 8, &rewarder, &mut quarry, vault, &mut miner_two);
+        assert!(daily_rewards_rate / 2 - claimed_one <= 1);
+        assert!(daily_rewards_rate / 2 - claimed_two <= 1);
+    }
+
+    #[test]
+    fn test_committed_rewards_shares() {
+        let daily_rewards_rate = to_unit(4_000);
+        let rewarder = &mut Rewarder {
+            bump: 254,
+            annual_rewards_rate: daily_rewards_rate * 365,
+            num_quarries: 2,
+            total_rewards_shares: 200,
+            ..Default::default()
+        };
+        let quarry_one = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            ..Default::default()
+        };
+        let quarry_two = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            ..Default::default()
+        };
+        let miner_vault_one = &mut MinerVault { balance: 0 };
+        let miner_vault_two = &mut MinerVault { balance: 0 };
+        let miner_one = &mut Miner::default();
+        let miner_two = &mut Miner::default();
+
+        let mut current_ts: i64 = 0;
+        let total_to_stake = to_unit(500);
+        sim_stake(
+            current_ts,
+            rewarder,
+            quarry_one,
+            miner_vault_one,
+            miner_one,
+            total_to_stake,
+        );
+        sim_stake(
+            current_ts,
+            rewarder,
+            quarry_two,
+            miner_vault_two,
+            miner_two,
+            total_to_stake,
+        );
+
+        // Stage a new share for quarry one after 2 days; nothing changes yet
+        current_ts += SECONDS_PER_DAY as i64 * 2;
+        let payroll = quarry_one.payroll(rewarder);
+        quarry_one
+            .update_rewards_internal(current_ts, rewarder, &payroll)
+            .unwrap();
+        rewarder.stage_rewards_share(quarry_one, 300).unwrap();
+        assert_eq!(rewarder.total_rewards_shares, 200);
+        assert_eq!(rewarder.pending_total_rewards_shares, 400);
+        assert_eq!(quarry_one.rewards_share, 100);
+
+        rewarder.commit_rewards_shares(current_ts).unwrap();
+        assert_eq!(rewarder.total_rewards_shares, 400);
+        assert_eq!(rewarder.num_quarries_pending_sync, 2);
+
+        // a quarry must be synchronized before staging again
+        assert!(rewarder.stage_rewards_share(quarry_two, 100).is_err());
+
+        // Neither quarry is updated until 2 days after the commit
+        current_ts += SECONDS_PER_DAY as i64 * 2;
+        let claimed_one = sim_claim(current_ts, rewarder, quarry_one, miner_vault_one, miner_one);
+        let claimed_two = sim_claim(current_ts, rewarder, quarry_two, miner_vault_two, miner_two);
+        assert!(daily_rewards_rate * 5 / 2 - claimed_one <= 1);
+        assert!(daily_rewards_rate * 3 / 2 - claimed_two <= 1);
+        assert_eq!(quarry_one.rewards_share, 300);
+        assert!(!quarry_one.has_pending_rewards_share);
+
+        // the next commit requires every quarry to be synchronized
+        let payroll = quarry_one.payroll(rewarder);
+        quarry_one
+            .update_rewards_internal(current_ts, rewarder, &payroll)
+            .unwrap();
+        quarry_one.sync_rewards_shares_generation(rewarder).unwrap();
+        rewarder.stage_rewards_share(quarry_one, 100).unwrap();
+        assert!(rewarder.commit_rewards_shares(current_ts).is_err());
+
+        let payroll = quarry_two.payroll(rewarder);
+        quarry_two
+            .update_rewards_internal(current_ts, rewarder, &payroll)
+            .unwrap();
+        quarry_two.sync_rewards_shares_generation(rewarder).unwrap();
+        rewarder.commit_rewards_shares(current_ts).unwrap();
+        assert_eq!(rewarder.total_rewards_shares, 200);
+    }
+
+    #[test]
+    fn test_unbonding() {
+        let quarry = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            unbonding_period: SECONDS_PER_DAY as i64 * 7,
+            ..Default::default()
+        };
+        let daily_rewards_rate = to_unit(1_000);
+        let rewarder = Rewarder {
+            bump: 254,
+            annual_rewards_rate: daily_rewards_rate * 365,
+            num_quarries: 1,
+            total_rewards_shares: quarry.rewards_share,
+            ..Default::default()
+        };
+        let miner_vault_one = &mut MinerVault { balance: 0 };
+        let miner_vault_two = &mut MinerVault { balance: 0 };
+        let miner_one = &mut Miner::default();
+        let miner_two = &mut Miner::default();
+
+        let mut current_ts: i64 = 0;
+        let total_to_stake = to_unit(500);
+        sim_stake(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_one,
+            miner_one,
+            total_to_stake,
+        );
+        sim_stake(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_two,
+            miner_two,
+            total_to_stake,
+        );
+
+        // Miner one unbonds half of its balance after 2 days
+        current_ts += SECONDS_PER_DAY as i64 * 2;
+        quarry
+            .process_unbond_request_internal(current_ts, &rewarder, miner_one, total_to_stake / 2)
+            .unwrap();
+        assert_eq!(miner_one.balance, total_to_stake / 2);
+        assert_eq!(miner_one.unbonding_balance, total_to_stake / 2);
+        assert_eq!(miner_one.unbonding_end_ts, SECONDS_PER_DAY as i64 * 9);
+        assert_eq!(quarry.total_tokens_deposited, total_to_stake * 3 / 2);
+        assert_eq!(miner_one.rewards_earned, daily_rewards_rate);
+
+        // unbonding tokens do not earn rewards
+        current_ts += SECONDS_PER_DAY as i64 * 3;
+        let claimed_one = sim_claim(current_ts, &rewarder, quarry, miner_vault_one, miner_one);
+        let claimed_two = sim_claim(current_ts, &rewarder, quarry, miner_vault_two, miner_two);
+        assert!(daily_rewards_rate * 2 - claimed_one <= 1);
+        assert!(daily_rewards_rate * 3 - claimed_two <= 1);
+
+        // unbonding more than the balance fails
+        assert!(quarry
+            .process_unbond_request_internal(current_ts, &rewarder, miner_one, total_to_stake)
+            .is_err());
+
+        // another request restarts the unbonding period
+        quarry
+            .process_unbond_request_internal(current_ts, &rewarder, miner_one, total_to_stake / 2)
+            .unwrap();
+        assert_eq!(miner_one.balance, 0);
+        assert_eq!(miner_one.unbonding_balance, total_to_stake);
+        assert_eq!(miner_one.unbonding_end_ts, SECONDS_PER_DAY as i64 * 12);
+    }
+
+    #[test]
+    fn test_early_exit_penalty() {
+        let quarry = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            early_exit_period: SECONDS_PER_DAY as i64,
+            early_exit_penalty_bps: MAX_BPS / 10,
+            ..Default::default()
+        };
+        // no emissions, so only penalties are earned
+        let rewarder = Rewarder {
+            bump: 254,
+            num_quarries: 1,
+            total_rewards_shares: quarry.rewards_share,
+            ..Default::default()
+        };
+        let miner_vault_one = &mut MinerVault { balance: 0 };
+        let miner_vault_two = &mut MinerVault { balance: 0 };
+        let miner_one = &mut Miner::default();
+        let miner_two = &mut Miner::default();
+
+        let mut current_ts: i64 = 0;
+        let total_to_stake = to_unit(500);
+        sim_stake(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_one,
+            miner_one,
+            total_to_stake,
+        );
+        current_ts += SECONDS_PER_DAY as i64 / 2;
+        sim_stake(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_two,
+            miner_two,
+            total_to_stake,
+        );
+
+        // Miner one may exit freely after the period, miner two may not
+        current_ts += SECONDS_PER_DAY as i64 / 2;
+        assert_eq!(
+            quarry
+                .compute_early_exit_penalty(miner_one, current_ts, total_to_stake)
+                .unwrap(),
+            0
+        );
+        let penalty = quarry
+            .compute_early_exit_penalty(miner_two, current_ts, total_to_stake)
+            .unwrap();
+        assert_eq!(penalty, total_to_stake / 10);
+
+        sim_withdraw(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_two,
+            miner_two,
+            total_to_stake,
+        );
+        assert!(quarry.redistribute_early_exit_penalty(penalty).unwrap());
+
+        // the penalty goes to the remaining miner
+        let claimed_one = sim_claim(current_ts, &rewarder, quarry, miner_vault_one, miner_one);
+        assert!(penalty - claimed_one <= 1);
+        assert_eq!(
+            sim_claim(current_ts, &rewarder, quarry, miner_vault_two, miner_two),
+            0
+        );
+
+        // nobody is left to receive a penalty
+        sim_withdraw(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_one,
+            miner_one,
+            total_to_stake,
+        );
+        assert!(!quarry.redistribute_early_exit_penalty(penalty).unwrap());
+    }
+
+    #[test]
+    fn test_stake_for_keeps_early_exit_period() {
+        let quarry = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            early_exit_period: SECONDS_PER_DAY as i64,
+            early_exit_penalty_bps: MAX_BPS / 10,
+            ..Default::default()
+        };
+        let rewarder = Rewarder {
+            bump: 254,
+            num_quarries: 1,
+            total_rewards_shares: quarry.rewards_share,
+            ..Default::default()
+        };
+        let miner_vault = &mut MinerVault { balance: 0 };
+        let miner = &mut Miner::default();
+
+        let mut current_ts: i64 = 0;
+        sim_stake(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault,
+            miner,
+            to_unit(500),
+        );
+
+        // a deposit by someone else does not restart the authority's early exit period,
+        // but is penalized itself
+        current_ts += SECONDS_PER_DAY as i64;
+        quarry
+            .process_stake_action_internal(
+                StakeAction::StakeFor,
+                current_ts,
+                &rewarder,
+                miner,
+                to_unit(100),
+            )
+            .unwrap();
+        assert_eq!(miner.last_stake_ts, 0);
+        assert_eq!(miner.balance, to_unit(600));
+        assert_eq!(
+            quarry
+                .compute_early_exit_penalty(miner, current_ts, to_unit(500))
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            quarry
+                .compute_early_exit_penalty(miner, current_ts, miner.balance)
+                .unwrap(),
+            to_unit(10)
+        );
+
+        // withdrawing the authority's own tokens leaves the deposit penalized
+        sim_withdraw(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault,
+            miner,
+            to_unit(450),
+        );
+        assert_eq!(miner.staked_for_balance, to_unit(100));
+        assert_eq!(
+            quarry
+                .compute_early_exit_penalty(miner, current_ts, to_unit(100))
+                .unwrap(),
+            to_unit(5)
+        );
+
+        // another deposit extends the early exit period of both deposits
+        current_ts += SECONDS_PER_DAY as i64 / 2;
+        quarry
+            .process_stake_action_internal(
+                StakeAction::StakeFor,
+                current_ts,
+                &rewarder,
+                miner,
+                to_unit(50),
+            )
+            .unwrap();
+        current_ts += SECONDS_PER_DAY as i64 / 2;
+        assert_eq!(
+            quarry
+                .compute_early_exit_penalty(miner, current_ts, miner.balance)
+                .unwrap(),
+            to_unit(15)
+        );
+        current_ts += SECONDS_PER_DAY as i64 / 2;
+        assert_eq!(
+            quarry
+                .compute_early_exit_penalty(miner, current_ts, miner.balance)
+                .unwrap(),
+            0
+        );
+        quarry
+            .process_stake_action_internal(StakeAction::StakeFor, current_ts, &rewarder, miner, 1)
+            .unwrap();
+        assert_eq!(miner.staked_for_balance, 1);
+
+        // a stake by the authority does
+        quarry
+            .process_stake_action_internal(StakeAction::Stake, current_ts, &rewarder, miner, 1)
+            .unwrap();
+        assert_eq!(miner.last_stake_ts, current_ts);
+        assert_eq!(
+            quarry
+                .compute_early_exit_penalty(miner, current_ts, to_unit(200))
+                .unwrap(),
+            to_unit(20)
+        );
+    }
+
+    #[test]
+    fn test_stake_for_fresh_miner_is_penalized() {
+        let quarry = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            early_exit_period: SECONDS_PER_DAY as i64,
+            early_exit_penalty_bps: MAX_BPS / 10,
+            ..Default::default()
+        };
+        let rewarder = Rewarder {
+            bump: 254,
+            num_quarries: 1,
+            total_rewards_shares: quarry.rewards_share,
+            ..Default::default()
+        };
+        let miner = &mut Miner::default();
+
+        // a miner funded only by someone else cannot withdraw without a penalty
+        let current_ts = 1_700_000_000;
+        quarry
+            .process_stake_action_internal(
+                StakeAction::StakeFor,
+                current_ts,
+                &rewarder,
+                miner,
+                to_unit(1_000),
+            )
+            .unwrap();
+        assert_eq!(miner.last_stake_ts, 0);
+        assert_eq!(
+            quarry
+                .compute_early_exit_penalty(miner, current_ts, to_unit(1_000))
+                .unwrap(),
+            to_unit(100)
+        );
+        quarry
+            .process_unbond_request_internal(current_ts, &rewarder, miner, to_unit(400))
+            .unwrap();
+        assert_eq!(miner.unbonding_penalty, to_unit(40));
+        assert_eq!(miner.staked_for_balance, to_unit(600));
+
+        // until the early exit period of the deposit is over
+        let current_ts = current_ts + SECONDS_PER_DAY as i64;
+        assert_eq!(
+            quarry
+                .compute_early_exit_penalty(miner, current_ts, to_unit(600))
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_migrate_keeps_early_exit_period() {
+        let from_quarry = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            early_exit_period: SECONDS_PER_DAY as i64,
+            early_exit_penalty_bps: MAX_BPS / 10,
+            ..Default::default()
+        };
+        let to_quarry = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            early_exit_period: SECONDS_PER_DAY as i64 * 2,
+            early_exit_penalty_bps: MAX_BPS / 10,
+            ..Default::default()
+        };
+        let rewarder = Rewarder {
+            bump: 254,
+            num_quarries: 2,
+            total_rewards_shares: 200,
+            ..Default::default()
+        };
+        let from_miner = &mut Miner::default();
+        let to_miner = &mut Miner::default();
+
+        let stake_ts = 1_700_000_000;
+        from_quarry
+            .process_stake_action_internal(
+                StakeAction::Stake,
+                stake_ts,
+                &rewarder,
+                from_miner,
+                to_unit(1_000),
+            )
+            .unwrap();
+
+        // the tokens are migrated once the source early exit period is over
+        let current_ts = stake_ts + SECONDS_PER_DAY as i64;
+        assert_eq!(
+            from_quarry
+                .compute_early_exit_penalty(from_miner, current_ts, to_unit(1_000))
+                .unwrap(),
+            0
+        );
+        from_quarry
+            .process_stake_action_internal(
+                StakeAction::Withdraw,
+                current_ts,
+                &rewarder,
+                from_miner,
+                to_unit(1_000),
+            )
+            .unwrap();
+        to_quarry
+            .process_stake_action_internal(
+                StakeAction::Migrate {
+                    stake_ts: from_miner.last_stake_ts,
+                },
+                current_ts,
+                &rewarder,
+                to_miner,
+                to_unit(1_000),
+            )
+            .unwrap();
+        assert_eq!(to_miner.last_stake_ts, stake_ts);
+        assert_eq!(to_miner.balance, to_unit(1_000));
+        assert_eq!(to_miner.staked_for_balance, 0);
+
+        // the destination early exit period runs from the original stake
+        assert_eq!(
+            to_quarry
+                .compute_early_exit_penalty(to_miner, current_ts, to_unit(1_000))
+                .unwrap(),
+            to_unit(100)
+        );
+        let current_ts = stake_ts + SECONDS_PER_DAY as i64 * 2;
+        assert_eq!(
+            to_quarry
+                .compute_early_exit_penalty(to_miner, current_ts, to_unit(1_000))
+                .unwrap(),
+            0
+        );
+
+        // a later stake of the destination miner is kept
+        to_quarry
+            .process_stake_action_internal(StakeAction::Stake, current_ts, &rewarder, to_miner, 1)
+            .unwrap();
+        to_quarry
+            .process_stake_action_internal(
+                StakeAction::Migrate { stake_ts },
+                current_ts,
+                &rewarder,
+                to_miner,
+                1,
+            )
+            .unwrap();
+        assert_eq!(to_miner.last_stake_ts, current_ts);
+    }
+
+    #[test]
+    fn test_early_exit_penalty_in_stake() {
+        let quarry = &mut Quarry {
+            token_mint_key: Pubkey::new_unique(),
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            early_exit_period: SECONDS_PER_DAY as i64,
+            early_exit_penalty_bps: MAX_BPS / 10,
+            ..Default::default()
+        };
+        let rewarder = Rewarder {
+            bump: 254,
+            rewards_token_mint: Pubkey::new_unique(),
+            annual_rewards_rate: to_unit(1_000) * 365,
+            num_quarries: 1,
+            total_rewards_shares: quarry.rewards_share,
+            ..Default::default()
+        };
+        assert!(quarry.redistributes_early_exit_penalty_in_stake(&rewarder));
+        let miner_vault_one = &mut MinerVault { balance: 0 };
+        let miner_vault_two = &mut MinerVault { balance: 0 };
+        let miner_vault_three = &mut MinerVault { balance: 0 };
+        let miner_one = &mut Miner::default();
+        let miner_two = &mut Miner::default();
+        let miner_three = &mut Miner::default();
+
+        let current_ts = SECONDS_PER_DAY as i64;
+        sim_stake(
+            0,
+            &rewarder,
+            quarry,
+            miner_vault_one,
+            miner_one,
+            to_unit(100),
+        );
+        sim_stake(
+            0,
+            &rewarder,
+            quarry,
+            miner_vault_two,
+            miner_two,
+            to_unit(300),
+        );
+        sim_stake(
+            current_ts / 2,
+            &rewarder,
+            quarry,
+            miner_vault_three,
+            miner_three,
+            to_unit(400),
+        );
+
+        // miner three exits early; the others split its penalty by balance
+        let penalty = quarry
+            .compute_early_exit_penalty(miner_three, current_ts, to_unit(400))
+            .unwrap();
+        assert_eq!(penalty, to_unit(40));
+        sim_withdraw(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_three,
+            miner_three,
+            to_unit(400),
+        );
+        assert!(quarry
+            .redistribute_early_exit_penalty_in_stake(penalty)
+            .unwrap());
+        // the rewards are unaffected
+        assert_eq!(quarry.total_rewards_accrued, to_unit(1_000));
+
+        for miner in [&mut *miner_one, &mut *miner_two, &mut *miner_three] {
+            quarry
+                .update_rewards_and_miner(miner, &rewarder, current_ts)
+                .unwrap();
+        }
+        assert!(to_unit(10) - miner_one.penalties_earned <= 1);
+        assert!(to_unit(30) - miner_two.penalties_earned <= 1);
+        assert_eq!(miner_three.penalties_earned, 0);
+
+        // penalties are only credited once
+        quarry
+            .update_rewards_and_miner(miner_one, &rewarder, current_ts * 2)
+            .unwrap();
+        assert!(to_unit(10) - miner_one.penalties_earned <= 1);
+
+        // nobody is left to receive a penalty
+        sim_withdraw(
+            current_ts * 2,
+            &rewarder,
+            quarry,
+            miner_vault_one,
+            miner_one,
+            to_unit(100),
+        );
+        sim_withdraw(
+            current_ts * 2,
+            &rewarder,
+            quarry,
+            miner_vault_two,
+            miner_two,
+            to_unit(300),
+        );
+        assert!(!quarry
+            .redistribute_early_exit_penalty_in_stake(penalty)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_transfer_miner() {
+        let quarry = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            max_lock_duration: SECONDS_PER_DAY as i64 * 10,
+            max_lock_boost_bps: MAX_BPS,
+            ..Default::default()
+        };
+        let daily_rewards_rate = to_unit(1_000);
+        let rewarder = Rewarder {
+            bump: 254,
+            annual_rewards_rate: daily_rewards_rate * 365,
+            num_quarries: 1,
+            total_rewards_shares: quarry.rewards_share,
+            ..Default::default()
+        };
+        let miner_vault_one = &mut MinerVault { balance: 0 };
+        let miner_vault_two = &mut MinerVault { balance: 0 };
+        let miner_one = &mut Miner::default();
+        let miner_two = &mut Miner::default();
+
+        let mut current_ts: i64 = 0;
+        let total_to_stake = to_unit(500);
+        sim_stake(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_one,
+            miner_one,
+            total_to_stake,
+        );
+        quarry
+            .process_lock_internal(
+                current_ts,
+                &rewarder,
+                miner_one,
+                SECONDS_PER_DAY as i64 * 10,
+            )
+            .unwrap();
+
+        current_ts += SECONDS_PER_DAY as i64;
+        quarry
+            .process_transfer_miner_internal(current_ts, &rewarder, miner_one, miner_two)
+            .unwrap();
+        assert_eq!(miner_one.balance, 0);
+        assert_eq!(miner_one.weighted_balance(), 0);
+        assert_eq!(miner_one.rewards_earned, 0);
+        assert_eq!(miner_two.balance, total_to_stake);
+        assert_eq!(miner_two.boosted_balance, total_to_stake);
+        assert_eq!(miner_two.lock_end_ts, SECONDS_PER_DAY as i64 * 10);
+        assert_eq!(quarry.total_weighted_tokens(), total_to_stake * 2);
+        assert!(daily_rewards_rate - miner_two.rewards_earned <= 1);
+
+        // the position keeps earning under the new miner
+        current_ts += SECONDS_PER_DAY as i64;
+        let claimed_two = sim_claim(current_ts, &rewarder, quarry, miner_vault_two, miner_two);
+        assert!(daily_rewards_rate * 2 - claimed_two <= 2);
+        assert_eq!(
+            sim_claim(current_ts, &rewarder, quarry, miner_vault_one, miner_one),
+            0
+        );
+
+        // the destination must be empty
+        sim_stake(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_one,
+            miner_one,
+            total_to_stake,
+        );
+        assert!(quarry
+            .process_transfer_miner_internal(current_ts, &rewarder, miner_two, miner_one)
+            .is_err());
+    }
+
+    #[test]
+    fn test_vault_funded_rewards() {
+        let quarry = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            ..Default::default()
+        };
+        let daily_rewards_rate = to_unit(1_000);
+        let rewarder = &mut Rewarder {
+            bump: 254,
+            num_quarries: 1,
+            total_rewards_shares: quarry.rewards_share,
+            rewards_vault: Pubkey::new_unique(),
+            ..Default::default()
+        };
+        rewarder
+            .set_annual_rewards_rate(daily_rewards_rate * 365, 0)
+            .unwrap();
+        rewarder
+            .fund_rewards_vault(daily_rewards_rate * 5, 0)
+            .unwrap();
+        assert_eq!(rewarder.funded_until_ts, SECONDS_PER_DAY as i64 * 5);
+
+        let miner_vault_one = &mut MinerVault { balance: 0 };
+        let miner_vault_two = &mut MinerVault { balance: 0 };
+        let miner_one = &mut Miner::default();
+        let miner_two = &mut Miner::default();
+
+        let mut current_ts: i64 = 0;
+        sim_stake(
+            current_ts,
+            rewarder,
+            quarry,
+            miner_vault_one,
+            miner_one,
+            to_unit(300),
+        );
+        sim_stake(
+            current_ts,
+            rewarder,
+            quarry,
+            miner_vault_two,
+            miner_two,
+            to_unit(100),
+        );
+
+        // rewards stop once the vault runs out
+        current_ts += SECONDS_PER_DAY as i64 * 10;
+        let claimed_one = sim_claim(current_ts, rewarder, quarry, miner_vault_one, miner_one);
+        let claimed_two = sim_claim(current_ts, rewarder, quarry, miner_vault_two, miner_two);
+        assert!(claimed_one + claimed_two <= daily_rewards_rate * 5);
+        assert!(claimed_one + claimed_two >= daily_rewards_rate * 5 - 2);
+        assert_eq!(quarry.last_update_ts, rewarder.funded_until_ts);
+        assert_eq!(quarry.total_rewards_claimed, claimed_one + claimed_two);
+        assert!(quarry.rewards_outstanding().unwrap() <= 2);
+
+        // funding again resumes from the time of funding, in a new rewards epoch
+        // which the quarry applies the next time it is updated
+        assert!(rewarder
+            .fund_rewards_vault(daily_rewards_rate * 2, current_ts)
+            .is_err());
+        quarry.sync_rewards_epoch(rewarder).unwrap();
+        assert_eq!(rewarder.num_quarries_pending_epoch, 0);
+        rewarder
+            .fund_rewards_vault(daily_rewards_rate * 2, current_ts)
+            .unwrap();
+        assert_eq!(rewarder.funded_until_ts, SECONDS_PER_DAY as i64 * 12);
+        assert_eq!(
+            rewarder.previous_funded_until_ts,
+            SECONDS_PER_DAY as i64 * 5
+        );
+        assert_eq!(rewarder.num_quarries_pending_epoch, 1);
+        assert_eq!(quarry.last_update_ts, SECONDS_PER_DAY as i64 * 5);
+
+        current_ts += SECONDS_PER_DAY as i64;
+        let claimed_one = sim_claim(current_ts, rewarder, quarry, miner_vault_one, miner_one);
+        let claimed_two = sim_claim(current_ts, rewarder, quarry, miner_vault_two, miner_two);
+        assert!(claimed_one + claimed_two <= daily_rewards_rate);
+        assert!(claimed_one + claimed_two >= daily_rewards_rate - 2);
+        assert!(quarry.total_rewards_claimed <= quarry.total_rewards_accrued);
+        assert!(quarry.total_rewards_accrued <= daily_rewards_rate * 6 + 2);
+
+        // the rate may change once the quarry is synchronized with the epoch;
+        // the rest of the funding is paid out twice as fast
+        assert!(rewarder
+            .set_annual_rewards_rate(daily_rewards_rate * 2 * 365, current_ts)
+            .is_err());
+        quarry.sync_rewards_epoch(rewarder).unwrap();
+        rewarder
+            .set_annual_rewards_rate(daily_rewards_rate * 2 * 365, current_ts)
+            .unwrap();
+        assert_eq!(
+            rewarder.funded_until_ts,
+            SECONDS_PER_DAY as i64 * 11 + SECONDS_PER_DAY as i64 / 2
+        );
+        current_ts += SECONDS_PER_DAY as i64 / 4;
+        let claimed_one = sim_claim(current_ts, rewarder, quarry, miner_vault_one, miner_one);
+        let claimed_two = sim_claim(current_ts, rewarder, quarry, miner_vault_two, miner_two);
+        assert!(claimed_one + claimed_two <= daily_rewards_rate / 2);
+        assert!(claimed_one + claimed_two >= daily_rewards_rate / 2 - 2);
+
+        current_ts += SECONDS_PER_DAY as i64 * 3;
+        let claimed_one = sim_claim(current_ts, rewarder, quarry, miner_vault_one, miner_one);
+        let claimed_two = sim_claim(current_ts, rewarder, quarry, miner_vault_two, miner_two);
+        assert!(claimed_one + claimed_two <= daily_rewards_rate / 2);
+        assert!(claimed_one + claimed_two >= daily_rewards_rate / 2 - 2);
+        assert_eq!(quarry.last_update_ts, rewarder.funded_until_ts);
+        assert!(quarry.total_rewards_accrued <= daily_rewards_rate * 7 + 2);
+    }
+
+    #[test]
+    fn test_rewards_epoch_catch_up() {
+        let daily_rewards_rate = to_unit(1_000);
+        let rewarder = &mut Rewarder {
+            bump: 254,
+            num_quarries: 2,
+            total_rewards_shares: 200,
+            rewards_vault: Pubkey::new_unique(),
+            annual_rewards_rate: daily_rewards_rate * 365,
+            ..Default::default()
+        };
+        rewarder
+            .fund_rewards_vault(daily_rewards_rate * 4, 0)
+            .unwrap();
+        let quarry_rate = rewarder.compute_quarry_annual_rewards_rate(100).unwrap();
+        let quarry_one = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            annual_rewards_rate: quarry_rate,
+            ..Default::default()
+        };
+        let quarry_two = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            annual_rewards_rate: quarry_rate,
+            ..Default::default()
+        };
+        let miner_vault_one = &mut MinerVault { balance: 0 };
+        let miner_vault_two = &mut MinerVault { balance: 0 };
+        let miner_one = &mut Miner::default();
+        let miner_two = &mut Miner::default();
+        sim_stake(
+            0,
+            rewarder,
+            quarry_one,
+            miner_vault_one,
+            miner_one,
+            to_unit(100),
+        );
+        sim_stake(
+            0,
+            rewarder,
+            quarry_two,
+            miner_vault_two,
+            miner_two,
+            to_unit(100),
+        );
+
+        // the rate is halved after a day, without updating either quarry
+        let mut current_ts = SECONDS_PER_DAY as i64;
+        rewarder
+            .set_annual_rewards_rate(daily_rewards_rate / 2 * 365, current_ts)
+            .unwrap();
+        assert_eq!(rewarder.funded_until_ts, SECONDS_PER_DAY as i64 * 7);
+        assert_eq!(rewarder.num_quarries_pending_epoch, 2);
+
+        // the vault runs out on day 7 and is funded again on day 9
+        current_ts = SECONDS_PER_DAY as i64 * 8;
+        let claimed_one = sim_claim(current_ts, rewarder, quarry_one, miner_vault_one, miner_one);
+        assert!(daily_rewards_rate / 2 + daily_rewards_rate * 3 / 2 - claimed_one <= 2);
+        quarry_one.sync_rewards_epoch(rewarder).unwrap();
+        current_ts += SECONDS_PER_DAY as i64;
+        assert!(rewarder
+            .fund_rewards_vault(daily_rewards_rate, current_ts)
+            .is_err());
+        let payroll = quarry_two.payroll(rewarder);
+        quarry_two
+            .update_rewards_internal(current_ts, rewarder, &payroll)
+            .unwrap();
+        quarry_two.sync_rewards_epoch(rewarder).unwrap();
+        rewarder
+            .fund_rewards_vault(daily_rewards_rate, current_ts)
+            .unwrap();
+        assert_eq!(rewarder.funded_until_ts, SECONDS_PER_DAY as i64 * 11);
+
+        // each quarry pays what was funded, and nothing for the time the vault was empty
+        current_ts = SECONDS_PER_DAY as i64 * 12;
+        let claimed_one =
+            claimed_one + sim_claim(current_ts, rewarder, quarry_one, miner_vault_one, miner_one);
+        let claimed_two = sim_claim(current_ts, rewarder, quarry_two, miner_vault_two, miner_two);
+        let total_funded = daily_rewards_rate * 5 / 2;
+        assert!(total_funded - claimed_one <= 3);
+        assert!(total_funded - claimed_two <= 3);
+        assert!(claimed_one <= total_funded);
+        assert!(claimed_two <= total_funded);
+    }
+
+    #[test]
+    fn test_allowlist() {
+        let authority = Pubkey::new_unique();
+        let other_authority = Pubkey::new_unique();
+        let leaf = crate::allowlist::compute_allowlist_leaf(&authority);
+        let other_leaf = crate::allowlist::compute_allowlist_leaf(&other_authority);
+
+        let quarry = &mut Quarry {
+            famine_ts: i64::MAX,
+            allowlist_root: leaf,
+            ..Default::default()
+        };
+        let rewarder = Rewarder::default();
+        let miner_vault = &mut MinerVault { balance: 0 };
+        let miner = &mut Miner {
+            authority,
+            ..Default::default()
+        };
+        let other_miner = &mut Miner {
+            authority: other_authority,
+            ..Default::default()
+        };
+
+        assert!(quarry.approve_miner(other_miner, &[]).is_err());
+        assert!(quarry
+            .process_stake_action_internal(StakeAction::Stake, 0, &rewarder, other_miner, 100)
+            .is_err());
+
+        quarry.approve_miner(miner, &[]).unwrap();
+        sim_stake(0, &rewarder, quarry, miner_vault, miner, 100);
+
+        // rotating the root requires approving again
+        let mut pair = [leaf, other_leaf];
+        pair.sort_unstable();
+        quarry.allowlist_root = anchor_lang::solana_program::keccak::hashv(&[&pair[0], &pair[1]]).0;
+        assert!(quarry
+            .process_stake_action_internal(StakeAction::Stake, 1, &rewarder, miner, 100)
+            .is_err());
+        assert!(quarry.approve_miner(miner, &[leaf]).is_err());
+        quarry.approve_miner(miner, &[other_leaf]).unwrap();
+        sim_stake(1, &rewarder, quarry, miner_vault, miner, 100);
+        quarry.approve_miner(other_miner, &[leaf]).unwrap();
+
+        // withdrawals are never gated
+        quarry.allowlist_root = [1; 32];
+        sim_withdraw(2, &rewarder, quarry, miner_vault, miner, 200);
+
+        // ungated quarries are open to all
+        quarry.allowlist_root = [0; 32];
+        sim_stake(3, &rewarder, quarry, miner_vault, miner, 100);
+    }
+
+    #[test]
+    fn test_deposit_caps() {
+        let quarry = &mut Quarry {
+            famine_ts: i64::MAX,
+            max_total_tokens_deposited: 1_000,
+            max_miner_balance: 600,
+            ..Default::default()
+        };
+        let rewarder = Rewarder::default();
+        let miner_vault_one = &mut MinerVault { balance: 0 };
+        let miner_vault_two = &mut MinerVault { balance: 0 };
+        let miner_one = &mut Miner::default();
+        let miner_two = &mut Miner::default();
+
+        sim_stake(0, &rewarder, quarry, miner_vault_one, miner_one, 600);
+        let exceeded: Result<()> = err!(MinerDepositCapExceeded);
+        assert_eq!(
+            quarry
+                .process_stake_action_internal(StakeAction::Stake, 0, &rewarder, miner_one, 1)
+                .into_cmp_error(),
+            exceeded.into_cmp_error()
+        );
+
+        sim_stake(0, &rewarder, quarry, miner_vault_two, miner_two, 400);
+        let exceeded: Result<()> = err!(QuarryDepositCapExceeded);
+        assert_eq!(
+            quarry
+                .process_stake_action_internal(StakeAction::Stake, 0, &rewarder, miner_two, 1)
+                .into_cmp_error(),
+            exceeded.into_cmp_error()
+        );
+
+        // withdrawing frees up room under the caps
+        sim_withdraw(0, &rewarder, quarry, miner_vault_one, miner_one, 100);
+        sim_stake(0, &rewarder, quarry, miner_vault_two, miner_two, 100);
+
+        // zero caps are unlimited
+        quarry.max_total_tokens_deposited = 0;
+        quarry.max_miner_balance = 0;
+        sim_stake(0, &rewarder, quarry, miner_vault_two, miner_two, 10_000);
+    }
+
+    #[test]
+    fn test_total_rewards_accrued_does_not_drift() {
+        let rewarder = Rewarder {
+            annual_rewards_rate: crate::payroll::SECONDS_PER_YEAR as u64 / 2,
+            num_quarries: 1,
+            total_rewards_shares: 100,
+            ..Default::default()
+        };
+        let quarry = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            annual_rewards_rate: rewarder.annual_rewards_rate,
+            total_tokens_deposited: 1,
+            ..Default::default()
+        };
+
+        // half a token is issued each second
+        for current_ts in 1..=101 {
+            let payroll = quarry.payroll(&rewarder);
+            quarry
+                .update_rewards_internal(current_ts, &rewarder, &payroll)
+                .unwrap();
+        }
+        assert_eq!(quarry.total_rewards_accrued, 50);
+        assert_eq!(
+            quarry.rewards_issued_remainder,
+            crate::payroll::REWARDS_DENOMINATOR / 2
+        );
+    }
+
+    #[test]
+    fn test_emergency_withdraw() {
+        let quarry = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            max_lock_duration: SECONDS_PER_DAY as i64 * 10,
+            max_lock_boost_bps: MAX_BPS,
+            unbonding_period: SECONDS_PER_DAY as i64,
+            ..Default::default()
+        };
+        let daily_rewards_rate = to_unit(1_000);
+        let rewarder = Rewarder {
+            bump: 254,
+            annual_rewards_rate: daily_rewards_rate * 365,
+            num_quarries: 1,
+            total_rewards_shares: quarry.rewards_share,
+            ..Default::default()
+        };
+        let miner_vault_one = &mut MinerVault { balance: 0 };
+        let miner_vault_two = &mut MinerVault { balance: 0 };
+        let miner_one = &mut Miner::default();
+        let miner_two = &mut Miner::default();
+
+        let mut current_ts: i64 = 0;
+        let total_to_stake = to_unit(500);
+        sim_stake(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_one,
+            miner_one,
+            total_to_stake,
+        );
+        sim_stake(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_two,
+            miner_two,
+            to_unit(800),
+        );
+        quarry
+            .process_unbond_request_internal(current_ts, &rewarder, miner_one, to_unit(100))
+            .unwrap();
+        quarry
+            .process_lock_internal(
+                current_ts,
+                &rewarder,
+                miner_one,
+                SECONDS_PER_DAY as i64 * 10,
+            )
+            .unwrap();
+
+        current_ts += SECONDS_PER_DAY as i64;
+        quarry
+            .update_rewards_and_miner(miner_one, &rewarder, current_ts)
+            .unwrap();
+        assert!(daily_rewards_rate / 2 - miner_one.rewards_earned <= 1);
+
+        // the lock, unbonding balance and rewards are all given up
+        let miner_rewards_earned = miner_one.rewards_earned;
+        let forfeited = quarry
+            .process_emergency_withdraw_internal(current_ts, &rewarder, miner_one)
+            .unwrap();
+        assert_eq!(forfeited, miner_rewards_earned);
+        assert_eq!(miner_one.balance, 0);
+        assert_eq!(miner_one.weighted_balance(), 0);
+        assert_eq!(miner_one.unbonding_balance, 0);
+        assert_eq!(miner_one.rewards_earned, 0);
+        assert_eq!(miner_one.lock_end_ts, 0);
+        assert_eq!(quarry.total_tokens_deposited, to_unit(800));
+        assert_eq!(quarry.total_weighted_tokens(), to_unit(800));
+
+        // the remaining miner is unaffected
+        current_ts += SECONDS_PER_DAY as i64;
+        let claimed_two = sim_claim(current_ts, &rewarder, quarry, miner_vault_two, miner_two);
+        assert!(daily_rewards_rate * 3 / 2 - claimed_two <= 2);
+        assert_eq!(
+            sim_claim(current_ts, &rewarder, quarry, miner_vault_one, miner_one),
+            0
+        );
+    }
+
+    #[test]
+    fn test_emergency_withdraw_checkpoints_quarry() {
+        let quarry = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            ..Default::default()
+        };
+        let daily_rewards_rate = to_unit(1_000);
+        let rewarder = Rewarder {
+            bump: 254,
+            annual_rewards_rate: daily_rewards_rate * 365,
+            num_quarries: 1,
+            total_rewards_shares: quarry.rewards_share,
+            ..Default::default()
+        };
+        let miner_vault_one = &mut MinerVault { balance: 0 };
+        let miner_vault_two = &mut MinerVault { balance: 0 };
+        let miner_one = &mut Miner::default();
+        let miner_two = &mut Miner::default();
+
+        let mut current_ts: i64 = 0;
+        sim_stake(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_one,
+            miner_one,
+            to_unit(500),
+        );
+        sim_stake(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault_two,
+            miner_two,
+            to_unit(500),
+        );
+
+        // the quarry is not updated before the withdrawal
+        current_ts += SECONDS_PER_DAY as i64;
+        let total_rewards_accrued = quarry.total_rewards_accrued;
+        let forfeited = quarry
+            .process_emergency_withdraw_internal(current_ts, &rewarder, miner_one)
+            .unwrap();
+        assert!(daily_rewards_rate / 2 - forfeited <= 1);
+        assert_eq!(quarry.last_update_ts, current_ts);
+        assert_eq!(miner_one.rewards_per_token_paid, 0);
+        assert_eq!(quarry.total_rewards_forfeited, forfeited);
+        assert!(quarry.total_rewards_accrued - total_rewards_accrued - daily_rewards_rate <= 1);
+        assert!(quarry.rewards_outstanding().unwrap() - daily_rewards_rate / 2 <= 1);
+
+        // the remaining miner only earns the whole rate after the withdrawal
+        current_ts += SECONDS_PER_DAY as i64;
+        let claimed_two = sim_claim(current_ts, &rewarder, quarry, miner_vault_two, miner_two);
+        assert!(claimed_two <= daily_rewards_rate * 3 / 2);
+        assert!(daily_rewards_rate * 3 / 2 - claimed_two <= 2);
+        assert!(quarry.total_rewards_claimed <= quarry.total_rewards_accrued);
+    }
+
+    #[test]
+    fn test_emergency_withdraw_skips_miner_update() {
+        let quarry = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            ..Default::default()
+        };
+        let rewarder = Rewarder {
+            bump: 254,
+            annual_rewards_rate: to_unit(1_000) * 365,
+            num_quarries: 1,
+            total_rewards_shares: quarry.rewards_share,
+            ..Default::default()
+        };
+        let miner_vault = &mut MinerVault { balance: 0 };
+        let miner = &mut Miner::default();
+
+        let mut current_ts: i64 = 0;
+        sim_stake(
+            current_ts,
+            &rewarder,
+            quarry,
+            miner_vault,
+            miner,
+            to_unit(500),
+        );
+
+        // a miner which cannot be updated may still withdraw
+        miner.rewards_per_token_paid = u128::MAX;
+        miner.rewards_earned = 1;
+        current_ts += SECONDS_PER_DAY as i64;
+        assert!(quarry
+            .update_rewards_and_miner(miner, &rewarder, current_ts)
+            .is_err());
+        let forfeited = quarry
+            .process_emergency_withdraw_internal(current_ts, &rewarder, miner)
+            .unwrap();
+        assert_eq!(forfeited, 1);
+        assert_eq!(quarry.last_update_ts, current_ts);
+        assert_eq!(quarry.total_tokens_deposited, 0);
+        assert_eq!(miner.balance, 0);
+    }
+
+    #[test]
+    fn test_rewards_totals() {
+        let rewarder = &mut Rewarder::default();
+        let quarry_one = &mut Quarry {
+            total_rewards_accrued: 100,
+            total_rewards_claimed: 40,
+            ..Default::default()
+        };
+        let quarry_two = &mut Quarry {
+            total_rewards_accrued: 50,
+            ..Default::default()
+        };
+        quarry_one.record_claim_fees(4).unwrap();
+
+        quarry_one.report_rewards_totals(rewarder).unwrap();
+        quarry_two.report_rewards_totals(rewarder).unwrap();
+        assert_eq!(rewarder.total_rewards_accrued, 150);
+        assert_eq!(rewarder.total_rewards_claimed, 40);
+        assert_eq!(rewarder.total_claim_fees, 4);
+        assert_eq!(rewarder.rewards_outstanding().unwrap(), 110);
+
+        // only the changes since the last report are added
+        quarry_one.total_rewards_accrued = 120;
+        quarry_one.report_rewards_totals(rewarder).unwrap();
+        quarry_one.report_rewards_totals(rewarder).unwrap();
+        assert_eq!(rewarder.total_rewards_accrued, 170);
+
+        // forfeited rewards are removed from the totals
+        quarry_two.total_rewards_accrued = 30;
+        quarry_two.report_rewards_totals(rewarder).unwrap();
+        assert_eq!(rewarder.total_rewards_accrued, 150);
+        assert_eq!(rewarder.rewards_outstanding().unwrap(), 110);
+    }
+
+    #[test]
+    fn test_rewards_totals_claim_between_reports() {
+        let daily_rewards_rate = to_unit(1_000);
+        let rewarder = &mut Rewarder {
+            bump: 254,
+            num_quarries: 1,
+            total_rewards_shares: 100,
+            annual_rewards_rate: daily_rewards_rate * 365,
+            ..Default::default()
+        };
+        let quarry = &mut Quarry {
+            famine_ts: i64::MAX,
+            rewards_share: 100,
+            annual_rewards_rate: daily_rewards_rate * 365,
+            ..Default::default()
+        };
+        let miner_vault = &mut MinerVault { balance: 0 };
+        let miner = &mut Miner::default();
+        sim_stake(0, rewarder, quarry, miner_vault, miner, to_unit(100));
+
+        let mut current_ts = SECONDS_PER_DAY as i64;
+        let payroll = quarry.payroll(rewarder);
+        quarry
+            .update_rewards_internal(current_ts, rewarder, &payroll)
+            .unwrap();
+        quarry.report_rewards_totals(rewarder).unwrap();
+        let reported_outstanding = rewarder.rewards_outstanding().unwrap();
+        assert!(daily_rewards_rate - reported_outstanding <= 1);
+
+        // a claim between reports claims more than was accrued as of the last report,
+        // but leaves the totals of the rewarder as they were
+        current_ts += SECONDS_PER_DAY as i64;
+        let claimed = sim_claim(current_ts, rewarder, quarry, miner_vault, miner);
+        assert!(claimed > reported_outstanding);
+        assert_eq!(
+            rewarder.rewards_outstanding().unwrap(),
+            reported_outstanding
+        );
+        assert!(quarry.rewards_outstanding().unwrap() <= reported_outstanding);
+
+        // the next report adds the accrued and claimed rewards together
+        quarry.report_rewards_totals(rewarder).unwrap();
+        assert_eq!(rewarder.total_rewards_accrued, quarry.total_rewards_accrued);
+        assert_eq!(rewarder.total_rewards_claimed, claimed);
+        assert_eq!(
+            rewarder.rewards_outstanding().unwrap(),
+            quarry.rewards_outstanding().unwrap()
+        );
+    }
 }
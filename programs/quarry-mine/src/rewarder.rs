@@ -3,9 +3,13 @@
 use anchor_lang::prelude::*;
 use num_traits::ToPrimitive;
 use spl_math::uint::U192;
+use std::cmp;
 use vipers::prelude::*;
 
-use crate::Rewarder;
+use crate::{
+    payroll::SECONDS_PER_YEAR, Quarry, Rewarder, DEFAULT_CLAIM_FEE_MILLIBPS, MAX_BPS,
+    MAX_REFERRAL_FEE_MILLIBPS,
+};
 
 impl Rewarder {
     /// Computes the amount of rewards a [crate::Quarry] should receive, annualized.
@@ -40,6 +44,228 @@ impl Rewarder {
             unwrap_int!(self.compute_quarry_annual_rewards_rate_unsafe(quarry_rewards_share));
         Ok(unwrap_int!(raw_rate.to_u64()))
     }
+
+    /// Returns true if rewards are paid out of the [Rewarder::rewards_vault]
+    /// rather than minted.
+    pub fn is_vault_funded(&self) -> bool {
+        self.rewards_vault != Pubkey::default()
+    }
+
+    /// Rewards accrued to the miners of the [Rewarder]'s quarries which had not been claimed
+    /// as of the last time each quarry was reported. See [Rewarder::total_rewards_accrued].
+    ///
+    /// Rewards accrued since a quarry was last reported are missing, while rewards claimed or
+    /// forfeited since then are still counted, so this is only an upper bound of the rewards
+    /// owed once no quarry accrues any further (see [Rewarder::has_final_rewards_totals]).
+    /// It cannot underflow: each quarry reports its accrued and claimed rewards together,
+    /// and a quarry never has more rewards claimed than accrued.
+    pub fn rewards_outstanding(&self) -> Result<u64> {
+        Ok(unwrap_int!(self
+            .total_rewards_accrued
+            .checked_sub(self.total_rewards_claimed)))
+    }
+
+    /// Extends [Rewarder::funded_until_ts] by the time `amount` rewards last at the current rate.
+    ///
+    /// If the vault had already run out, rewards resume from `current_ts` in a new rewards epoch:
+    /// nothing is paid for the time the vault was empty.
+    pub fn fund_rewards_vault(&mut self, amount: u64, current_ts: i64) -> Result<()> {
+        invariant!(self.annual_rewards_rate != 0, InvalidAnnualRewardsRate);
+        if self.funded_until_ts < current_ts {
+            self.start_rewards_epoch(current_ts)?;
+        }
+        let funded_seconds = unwrap_int!((amount as u128)
+            .checked_mul(SECONDS_PER_YEAR)
+            .and_then(|v| v.checked_div(self.annual_rewards_rate.into()))
+            .and_then(|v| v.to_i64()));
+        self.funded_until_ts =
+            unwrap_int!(cmp::max(self.funded_until_ts, current_ts).checked_add(funded_seconds));
+        Ok(())
+    }
+
+    /// Sets the [Rewarder::annual_rewards_rate].
+    ///
+    /// The remaining funding of a vault-funded [Rewarder] is rescaled to the new rate
+    /// in a new rewards epoch.
+    pub fn set_annual_rewards_rate(&mut self, new_rate: u64, current_ts: i64) -> Result<()> {
+        if self.is_vault_funded() {
+            invariant!(new_rate != 0, InvalidAnnualRewardsRate);
+            self.start_rewards_epoch(current_ts)?;
+            if self.funded_until_ts > current_ts {
+                let remaining_seconds =
+                    unwrap_int!(self.funded_until_ts.checked_sub(current_ts)) as u128;
+                let rescaled_seconds = unwrap_int!(remaining_seconds
+                    .checked_mul(self.annual_rewards_rate.into())
+                    .and_then(|v| v.checked_div(new_rate.into()))
+                    .and_then(|v| v.to_i64()));
+                self.funded_until_ts = unwrap_int!(current_ts.checked_add(rescaled_seconds));
+            }
+        }
+        self.annual_rewards_rate = new_rate;
+        Ok(())
+    }
+
+    /// Starts a new rewards epoch of a vault-funded [Rewarder] at `current_ts`.
+    ///
+    /// Each [Quarry] accrues under the previous rate and funding up to `current_ts`
+    /// the next time it is updated, so the [Quarry]s do not need to be updated at once.
+    /// Every [Quarry] must be synchronized before the next epoch or rewards shares commit.
+    pub fn start_rewards_epoch(&mut self, current_ts: i64) -> Result<()> {
+        invariant!(self.num_quarries_pending_epoch == 0, RewardsEpochNotSynced);
+        invariant!(self.num_quarries_pending_sync == 0, RewardsSharesNotSynced);
+        self.rewards_epoch = unwrap_int!(self.rewards_epoch.checked_add(1));
+        self.rewards_epoch_ts = current_ts;
+        self.previous_funded_until_ts = self.funded_until_ts;
+        self.num_quarries_pending_epoch =
+            unwrap_int!(self.num_quarries.checked_sub(self.num_retired_quarries));
+        Ok(())
+    }
+
+    /// Returns true if every [Quarry] has reported its rewards since the
+    /// [Rewarder::rewards_vault] ran out, so that none of them accrue any further.
+    pub fn has_final_rewards_totals(&self) -> bool {
+        self.rewards_epoch_ts >= self.funded_until_ts && self.num_quarries_pending_epoch == 0
+    }
+
+    /// Fee charged on each claim, in milliBPS.
+    pub fn effective_claim_fee_millibps(&self) -> u64 {
+        if self.is_claim_fee_set {
+            self.claim_fee_millibps.min(self.max_claim_fee_millibps)
+        } else {
+            self.max_claim_fee_millibps
+        }
+    }
+
+    /// Computes the fee charged on claiming `amount_claimable` rewards.
+    pub fn compute_claim_fee(&self, amount_claimable: u64) -> Result<u64> {
+        invariant!(
+            self.max_claim_fee_millibps < MAX_BPS * DEFAULT_CLAIM_FEE_MILLIBPS,
+            InvalidMaxClaimFee
+        );
+        Ok(unwrap_int!(::u128::mul_div_u64(
+            amount_claimable,
+            self.effective_claim_fee_millibps(),
+            MAX_BPS * DEFAULT_CLAIM_FEE_MILLIBPS
+        )))
+    }
+
+    /// Computes the referral fee charged on claiming `amount_claimable` rewards,
+    /// on top of the claim fee.
+    pub fn compute_referral_fee(&self, amount_claimable: u64) -> Result<u64> {
+        invariant!(
+            self.referral_fee_millibps <= MAX_REFERRAL_FEE_MILLIBPS,
+            InvalidReferralFee
+        );
+        Ok(unwrap_int!(::u128::mul_div_u64(
+            amount_claimable,
+            self.referral_fee_millibps,
+            MAX_BPS * DEFAULT_CLAIM_FEE_MILLIBPS
+        )))
+    }
+
+    /// Sets the [Rewarder::referral_fee_millibps], which may not exceed [MAX_REFERRAL_FEE_MILLIBPS].
+    pub fn set_referral_fee(&mut self, referral_fee_millibps: u64) -> Result<()> {
+        invariant!(
+            referral_fee_millibps <= MAX_REFERRAL_FEE_MILLIBPS,
+            InvalidReferralFee
+        );
+        self.referral_fee_millibps = referral_fee_millibps;
+        Ok(())
+    }
+
+    /// Sets the [Rewarder::claim_fee_millibps], which may not exceed the maximum.
+    pub fn set_claim_fee(&mut self, claim_fee_millibps: u64) -> Result<()> {
+        invariant!(
+            claim_fee_millibps <= self.max_claim_fee_millibps,
+            ClaimFeeExceedsMax
+        );
+        self.claim_fee_millibps = claim_fee_millibps;
+        self.is_claim_fee_set = true;
+        Ok(())
+    }
+
+    /// Lowers the [Rewarder::max_claim_fee_millibps], clamping the claim fee to it.
+    pub fn lower_max_claim_fee(&mut self, max_claim_fee_millibps: u64) -> Result<()> {
+        invariant!(
+            max_claim_fee_millibps <= self.max_claim_fee_millibps,
+            InvalidMaxClaimFee
+        );
+        self.max_claim_fee_millibps = max_claim_fee_millibps;
+        if self.claim_fee_millibps > max_claim_fee_millibps {
+            self.claim_fee_millibps = max_claim_fee_millibps;
+        }
+        Ok(())
+    }
+
+    /// Returns true if rewards shares are staged or have not been applied by every [Quarry].
+    pub fn has_pending_rewards_shares(&self) -> bool {
+        self.num_staged_quarries != 0 || self.num_quarries_pending_sync != 0
+    }
+
+    /// Stages a new rewards share for a [Quarry], to be applied on the next commit.
+    /// The [Quarry] must be synchronized with the latest commit.
+    pub fn stage_rewards_share(&mut self, quarry: &mut Quarry, new_share: u64) -> Result<()> {
+        invariant!(
+            quarry.rewards_shares_generation == self.rewards_shares_generation,
+            QuarryNotSynced
+        );
+        if self.num_staged_quarries == 0 {
+            self.pending_total_rewards_shares = self.total_rewards_shares;
+        }
+
+        let previous_share = if quarry.has_pending_rewards_share {
+            quarry.pending_rewards_share
+        } else {
+            quarry.rewards_share
+        };
+        self.pending_total_rewards_shares = unwrap_int!(self
+            .pending_total_rewards_shares
+            .checked_add(new_share)
+            .and_then(|v| v.checked_sub(previous_share)));
+
+        if !quarry.has_pending_rewards_share {
+            self.num_staged_quarries = unwrap_int!(self.num_staged_quarries.checked_add(1));
+            quarry.has_pending_rewards_share = true;
+        }
+        quarry.pending_rewards_share = new_share;
+
+        Ok(())
+    }
+
+    /// Commits all staged rewards shares at once.
+    ///
+    /// Each [Quarry] applies its new rewards rate from `current_ts` onwards the next time it
+    /// is updated. Every [Quarry] must be synchronized before the following commit
+    /// or rewards epoch.
+    pub fn commit_rewards_shares(&mut self, current_ts: i64) -> Result<()> {
+        invariant!(self.num_quarries_pending_sync == 0, RewardsSharesNotSynced);
+        invariant!(self.num_quarries_pending_epoch == 0, RewardsEpochNotSynced);
+        invariant!(self.num_staged_quarries != 0, NoStagedRewardsShares);
+
+        self.total_rewards_shares = self.pending_total_rewards_shares;
+        self.pending_total_rewards_shares = 0;
+        self.num_staged_quarries = 0;
+        self.rewards_shares_generation = unwrap_int!(self.rewards_shares_generation.checked_add(1));
+        self.last_rewards_shares_commit_ts = current_ts;
+        self.num_quarries_pending_sync =
+            unwrap_int!(self.num_quarries.checked_sub(self.num_retired_quarries));
+
+        Ok(())
+    }
+
+    /// Records that a [Quarry] was closed, so that it is no longer expected to synchronize.
+    pub fn retire_quarry(&mut self, quarry: &Quarry) -> Result<()> {
+        if quarry.rewards_shares_generation != self.rewards_shares_generation {
+            self.num_quarries_pending_sync =
+                unwrap_int!(self.num_quarries_pending_sync.checked_sub(1));
+        }
+        if quarry.rewards_epoch != self.rewards_epoch {
+            self.num_quarries_pending_epoch =
+                unwrap_int!(self.num_quarries_pending_epoch.checked_sub(1));
+        }
+        self.num_retired_quarries = unwrap_int!(self.num_retired_quarries.checked_add(1));
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -132,11 +358,7 @@ mod tests {
 
         const MAX_EPSILON: u64 = 30;
         let num_quarries = rewarder.num_quarries as u64;
-        let epsilon: u64 = if diff > num_quarries / 2 {
-            diff - num_quarries / 2
-        } else {
-            num_quarries / 2 - diff
-        };
+        let epsilon: u64 = diff.abs_diff(num_quarries / 2);
         assert!(
             epsilon <= MAX_EPSILON,
             "diff: {}, num_quarries / 2: {}, epsilon: {}",
@@ -146,6 +368,181 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_claim_fee() {
+        let rewarder = &mut Rewarder {
+            max_claim_fee_millibps: DEFAULT_CLAIM_FEE_MILLIBPS,
+            ..Default::default()
+        };
+        // legacy rewarders charge the maximum
+        assert_eq!(rewarder.compute_claim_fee(1_000_000).unwrap(), 100);
+
+        assert!(rewarder
+            .set_claim_fee(DEFAULT_CLAIM_FEE_MILLIBPS + 1)
+            .is_err());
+        rewarder.set_claim_fee(500).unwrap();
+        assert_eq!(rewarder.compute_claim_fee(1_000_000).unwrap(), 50);
+        rewarder.set_claim_fee(0).unwrap();
+        assert_eq!(rewarder.compute_claim_fee(1_000_000).unwrap(), 0);
+
+        // the maximum may only be lowered, and clamps the fee
+        rewarder.set_claim_fee(800).unwrap();
+        assert!(rewarder
+            .lower_max_claim_fee(DEFAULT_CLAIM_FEE_MILLIBPS + 1)
+            .is_err());
+        rewarder.lower_max_claim_fee(300).unwrap();
+        assert_eq!(rewarder.claim_fee_millibps, 300);
+        assert_eq!(rewarder.compute_claim_fee(1_000_000).unwrap(), 30);
+
+        // referral fees are charged on the amount claimed, up to a small maximum
+        assert!(rewarder
+            .set_referral_fee(MAX_REFERRAL_FEE_MILLIBPS + 1)
+            .is_err());
+        rewarder
+            .set_referral_fee(MAX_REFERRAL_FEE_MILLIBPS)
+            .unwrap();
+        assert_eq!(rewarder.compute_referral_fee(1_000_000).unwrap(), 1_000);
+        rewarder.set_referral_fee(500).unwrap();
+        assert_eq!(rewarder.compute_referral_fee(1_000_000).unwrap(), 50);
+        assert_eq!(rewarder.compute_claim_fee(1_000_000).unwrap(), 30);
+    }
+
+    #[test]
+    fn test_rewards_vault_funding() {
+        let mut rewarder = Rewarder {
+            rewards_vault: Pubkey::new_unique(),
+            funded_until_ts: 1_000,
+            ..Default::default()
+        };
+        assert!(rewarder.fund_rewards_vault(1_000, 1_000).is_err());
+
+        rewarder
+            .set_annual_rewards_rate(SECONDS_PER_YEAR as u64 * 2, 1_000)
+            .unwrap();
+        // nothing was funded, so there is nothing to rescale
+        assert_eq!(rewarder.funded_until_ts, 1_000);
+
+        rewarder.fund_rewards_vault(1_000, 1_000).unwrap();
+        assert_eq!(rewarder.funded_until_ts, 1_500);
+
+        // halving the rate doubles the remaining time
+        rewarder
+            .set_annual_rewards_rate(SECONDS_PER_YEAR as u64, 1_300)
+            .unwrap();
+        assert_eq!(rewarder.funded_until_ts, 1_700);
+        assert!(rewarder.set_annual_rewards_rate(0, 1_300).is_err());
+
+        // funding before the vault runs out extends the remaining time
+        rewarder.fund_rewards_vault(200, 1_600).unwrap();
+        assert_eq!(rewarder.funded_until_ts, 1_900);
+
+        // once exhausted, funding resumes from the current time
+        rewarder
+            .set_annual_rewards_rate(SECONDS_PER_YEAR as u64 * 4, 2_000)
+            .unwrap();
+        assert_eq!(rewarder.funded_until_ts, 1_900);
+        rewarder.fund_rewards_vault(400, 2_000).unwrap();
+        assert_eq!(rewarder.funded_until_ts, 2_100);
+    }
+
+    #[test]
+    fn test_rewards_epochs() {
+        let rewarder = &mut Rewarder {
+            num_quarries: 2,
+            total_rewards_shares: 200,
+            rewards_vault: Pubkey::new_unique(),
+            annual_rewards_rate: SECONDS_PER_YEAR as u64,
+            ..Default::default()
+        };
+        let quarry_one = &mut Quarry {
+            rewards_share: 100,
+            ..Default::default()
+        };
+        let quarry_two = &mut Quarry {
+            rewards_share: 100,
+            ..Default::default()
+        };
+        rewarder.fund_rewards_vault(1_000, 0).unwrap();
+        assert_eq!(rewarder.rewards_epoch, 0);
+        assert!(!rewarder.has_final_rewards_totals());
+
+        // funding after the vault ran out starts an epoch which every quarry must apply
+        rewarder.fund_rewards_vault(1_000, 2_000).unwrap();
+        assert_eq!(rewarder.rewards_epoch, 1);
+        assert_eq!(rewarder.rewards_epoch_ts, 2_000);
+        assert_eq!(rewarder.previous_funded_until_ts, 1_000);
+        assert_eq!(rewarder.num_quarries_pending_epoch, 2);
+        assert!(rewarder
+            .set_annual_rewards_rate(SECONDS_PER_YEAR as u64 * 2, 2_500)
+            .is_err());
+        rewarder.stage_rewards_share(quarry_one, 200).unwrap();
+        assert!(rewarder.commit_rewards_shares(2_500).is_err());
+
+        // a retired quarry which did not sync no longer blocks the next epoch
+        quarry_one.sync_rewards_epoch(rewarder).unwrap();
+        quarry_one.sync_rewards_epoch(rewarder).unwrap();
+        assert_eq!(rewarder.num_quarries_pending_epoch, 1);
+        rewarder.retire_quarry(quarry_two).unwrap();
+        assert_eq!(rewarder.num_quarries_pending_epoch, 0);
+        rewarder.commit_rewards_shares(2_500).unwrap();
+        assert!(rewarder
+            .set_annual_rewards_rate(SECONDS_PER_YEAR as u64 * 2, 2_500)
+            .is_err());
+        quarry_one.sync_rewards_shares_generation(rewarder).unwrap();
+
+        // the totals are final once every quarry applied an epoch after the vault ran out
+        rewarder
+            .set_annual_rewards_rate(SECONDS_PER_YEAR as u64 * 2, 2_500)
+            .unwrap();
+        assert_eq!(rewarder.funded_until_ts, 2_750);
+        assert!(!rewarder.has_final_rewards_totals());
+        quarry_one.sync_rewards_epoch(rewarder).unwrap();
+        rewarder.start_rewards_epoch(3_000).unwrap();
+        assert!(!rewarder.has_final_rewards_totals());
+        quarry_one.sync_rewards_epoch(rewarder).unwrap();
+        assert!(rewarder.has_final_rewards_totals());
+    }
+
+    #[test]
+    fn test_retire_quarry() {
+        let rewarder = &mut Rewarder {
+            num_quarries: 3,
+            total_rewards_shares: 300,
+            ..Default::default()
+        };
+        let stale_quarry = &mut Quarry {
+            rewards_share: 100,
+            ..Default::default()
+        };
+        let synced_quarry = &mut Quarry {
+            rewards_share: 100,
+            ..Default::default()
+        };
+        rewarder.stage_rewards_share(stale_quarry, 0).unwrap();
+        rewarder.stage_rewards_share(synced_quarry, 0).unwrap();
+        rewarder.commit_rewards_shares(0).unwrap();
+        assert_eq!(rewarder.num_quarries_pending_sync, 3);
+
+        // a retired quarry which did not sync no longer blocks the next commit
+        synced_quarry
+            .sync_rewards_shares_generation(rewarder)
+            .unwrap();
+        rewarder.retire_quarry(synced_quarry).unwrap();
+        rewarder.retire_quarry(stale_quarry).unwrap();
+        assert_eq!(rewarder.num_quarries_pending_sync, 1);
+        assert_eq!(rewarder.num_retired_quarries, 2);
+
+        let quarry = &mut Quarry {
+            rewards_share: 100,
+            ..Default::default()
+        };
+        quarry.sync_rewards_shares_generation(rewarder).unwrap();
+        rewarder.stage_rewards_share(quarry, 200).unwrap();
+        rewarder.commit_rewards_shares(0).unwrap();
+        assert_eq!(rewarder.total_rewards_shares, 200);
+        assert_eq!(rewarder.num_quarries_pending_sync, 1);
+    }
+
     proptest! {
         #[test]
         fn test_compute_rewards_rate_when_total_rewards_shares_is_zero(
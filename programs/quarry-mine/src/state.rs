@@ -3,7 +3,8 @@
 use crate::*;
 
 /// Controls token rewards distribution to all [Quarry]s.
-/// The [Rewarder] is also the [quarry_mint_wrapper::Minter] registered to the [quarry_mint_wrapper::MintWrapper].
+/// The [Rewarder] is also the [quarry_mint_wrapper::Minter] registered to the [quarry_mint_wrapper::MintWrapper],
+/// unless it pays rewards out of its [Rewarder::rewards_vault].
 #[account]
 #[derive(Copy, Default, Debug)]
 pub struct Rewarder {
@@ -42,10 +43,112 @@ pub struct Rewarder {
     pub pause_authority: Pubkey,
     /// If true, all instructions on the [Rewarder] are paused other than [quarry_mine::unpause].
     pub is_paused: bool,
+
+    /// Schedule of multipliers applied to the [Rewarder::annual_rewards_rate].
+    /// If empty, the rate is constant.
+    pub emission_schedule: EmissionSchedule,
+
+    /// Total rewards shares after the staged shares are committed.
+    /// Only meaningful while [Rewarder::num_staged_quarries] is nonzero.
+    pub pending_total_rewards_shares: u64,
+    /// Number of [Quarry]s with a staged rewards share.
+    pub num_staged_quarries: u16,
+    /// Number of rewards share commits.
+    pub rewards_shares_generation: u64,
+    /// When the last rewards share commit took place.
+    pub last_rewards_shares_commit_ts: i64,
+    /// Number of [Quarry]s which have not been synchronized since the last commit.
+    /// The next commit may only take place once this is zero.
+    pub num_quarries_pending_sync: u16,
+
+    /// Number of [Quarry]s which have been closed.
+    /// These are still counted by [Rewarder::num_quarries].
+    pub num_retired_quarries: u16,
+
+    /// Fee charged on each claim, in milliBPS.
+    /// Only applies if [Rewarder::is_claim_fee_set]; otherwise the
+    /// [Rewarder::max_claim_fee_millibps] is charged.
+    pub claim_fee_millibps: u64,
+    /// True if the [addresses::FEE_SETTER] has set the [Rewarder::claim_fee_millibps].
+    pub is_claim_fee_set: bool,
+
+    /// Fee charged on each claim with a referrer and sent to the referrer, in milliBPS.
+    /// It is charged on top of the claim fee. May not exceed [crate::MAX_REFERRAL_FEE_MILLIBPS].
+    pub referral_fee_millibps: u64,
+
+    /// Token account holding the rewards of a vault-funded [Rewarder].
+    /// If default, rewards are minted through the [Rewarder::mint_wrapper].
+    pub rewards_vault: Pubkey,
+    /// Timestamp until which the [Rewarder::rewards_vault] funds rewards at the current rate.
+    /// [Quarry]s stop accruing rewards after this time.
+    pub funded_until_ts: i64,
+    /// Bitmask of the [crate::PAUSE_SCOPE_STAKE], [crate::PAUSE_SCOPE_WITHDRAW] and
+    /// [crate::PAUSE_SCOPE_CLAIM] actions paused on every [Quarry] of the [Rewarder].
+    pub paused_scopes: u8,
+    /// Sum of the [Quarry::total_rewards_accrued] of the [Rewarder]'s quarries,
+    /// as of the last time each quarry was reported.
+    ///
+    /// This is not live: a quarry is only reported by instructions which update it together
+    /// with the [Rewarder], such as [quarry_mine::update_quarry_rewards] or
+    /// [quarry_mine::update_quarry_rewards_batch], and not when miners stake or claim.
+    /// Between reports, this is a lower bound of the rewards accrued.
+    pub total_rewards_accrued: u64,
+    /// Sum of the [Quarry::total_rewards_claimed] of the [Rewarder]'s quarries,
+    /// as of the last time each quarry was reported.
+    /// Like [Rewarder::total_rewards_accrued], this is a lower bound between reports.
+    pub total_rewards_claimed: u64,
+    /// Sum of the [Quarry::total_claim_fees] of the [Rewarder]'s quarries,
+    /// as of the last time each quarry was reported.
+    /// Like [Rewarder::total_rewards_accrued], this is a lower bound between reports.
+    pub total_claim_fees: u64,
+
+    /// Number of rewards epochs of a vault-funded [Rewarder]. An epoch starts whenever the
+    /// [Rewarder::annual_rewards_rate] is set, or the [Rewarder::rewards_vault] is funded
+    /// after running out.
+    pub rewards_epoch: u64,
+    /// When the latest rewards epoch started.
+    pub rewards_epoch_ts: i64,
+    /// The [Rewarder::funded_until_ts] before the latest rewards epoch started.
+    pub previous_funded_until_ts: i64,
+    /// Number of [Quarry]s which have not been synchronized since the latest rewards epoch.
+    /// The next epoch may only start once this is zero.
+    pub num_quarries_pending_epoch: u16,
 }
 
 impl Rewarder {
-    pub const LEN: usize = 32 + 1 + 32 + 32 + 2 + 8 + 8 + 32 + 32 + 32 + 8 + 32 + 1;
+    pub const LEN: usize = 32
+        + 1
+        + 32
+        + 32
+        + 2
+        + 8
+        + 8
+        + 32
+        + 32
+        + 32
+        + 8
+        + 32
+        + 1
+        + EmissionSchedule::LEN
+        + 8
+        + 2
+        + 8
+        + 8
+        + 2
+        + 2
+        + 8
+        + 1
+        + 8
+        + 32
+        + 8
+        + 1
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 2;
 
     /// Asserts that this [Rewarder] is not paused.
     pub fn assert_not_paused(&self) -> Result<()> {
@@ -54,6 +157,40 @@ impl Rewarder {
     }
 }
 
+/// Maximum number of [EmissionPoint]s in an [EmissionSchedule].
+pub const MAX_EMISSION_POINTS: usize = 16;
+
+/// A piecewise linear schedule of multipliers on a [Rewarder]'s rewards rate.
+///
+/// The multiplier is interpolated linearly between consecutive points.
+/// Two points at the same timestamp form a step. Before the first point
+/// and after the last point, the multiplier is that of the nearest point.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub struct EmissionSchedule {
+    /// Number of [EmissionPoint]s in use.
+    pub num_points: u8,
+    /// Points of the schedule, sorted by timestamp.
+    pub points: [EmissionPoint; 16],
+}
+
+impl EmissionSchedule {
+    pub const LEN: usize = 1 + EmissionPoint::LEN * MAX_EMISSION_POINTS;
+}
+
+/// A point of an [EmissionSchedule].
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub struct EmissionPoint {
+    /// Timestamp of the point.
+    pub ts: i64,
+    /// Multiplier on the rewards rate at this point, in BPS.
+    /// May not exceed [crate::MAX_BPS].
+    pub multiplier_bps: u64,
+}
+
+impl EmissionPoint {
+    pub const LEN: usize = 8 + 8;
+}
+
 /// A pool which distributes tokens to its [Miner]s.
 #[account]
 #[derive(Copy, Default)]
@@ -82,12 +219,229 @@ pub struct Quarry {
 
     /// Total number of tokens deposited into the quarry.
     pub total_tokens_deposited: u64,
-    /// Number of [Miner]s.
+    /// Number of [Miner]s created, including closed ones.
+    /// This is the [Miner::index] of the next [Miner].
     pub num_miners: u64,
+
+    /// Secondary [RewardStream]s of the quarry.
+    /// Unused slots have a default [RewardStream::mint_wrapper].
+    pub reward_streams: [RewardStream; 3],
+
+    /// Sum of the [Miner::boosted_balance]s of the quarry's miners.
+    pub total_boosted_tokens: u64,
+    /// Maximum duration, in seconds, a [Miner] may lock its balance for.
+    /// If zero, locking is disabled.
+    pub max_lock_duration: i64,
+    /// Boost granted to a [Miner] locking for [Quarry::max_lock_duration], in BPS.
+    /// Shorter locks receive a proportional boost.
+    pub max_lock_boost_bps: u64,
+
+    /// Rewards share applied on the next [quarry_mine::commit_rewards_shares].
+    pub pending_rewards_share: u64,
+    /// True if [Quarry::pending_rewards_share] is staged.
+    pub has_pending_rewards_share: bool,
+    /// The [Rewarder::rewards_shares_generation] the quarry was last synchronized with.
+    pub rewards_shares_generation: u64,
+
+    /// Seconds between a [Miner] requesting an unbond and being able to withdraw it.
+    /// If zero, [quarry_mine::withdraw_tokens] may be used directly.
+    pub unbonding_period: i64,
+
+    /// Seconds after a [Miner]'s last stake during which withdrawals are penalized.
+    /// If zero, there is no penalty.
+    pub early_exit_period: i64,
+    /// Penalty charged on early withdrawals, in BPS of the amount withdrawn.
+    pub early_exit_penalty_bps: u64,
+    /// Token account which receives early exit penalties.
+    /// If default, penalties are burned and redistributed to the remaining [Miner]s as rewards.
+    pub early_exit_treasury: Pubkey,
+
+    /// Total rewards accrued to the quarry's miners, rounded down.
+    /// The fraction left over is carried in [Quarry::rewards_issued_remainder].
+    pub total_rewards_accrued: u64,
+    /// Total rewards claimed by the quarry's miners, including fees.
+    pub total_rewards_claimed: u64,
+    /// Merkle root of the authorities allowed to stake into the quarry.
+    /// If zero, anyone may stake.
+    pub allowlist_root: [u8; 32],
+    /// Maximum [Quarry::total_tokens_deposited] after a stake. If zero, there is no cap.
+    pub max_total_tokens_deposited: u64,
+    /// Maximum [Miner::balance] after a stake. If zero, there is no cap.
+    pub max_miner_balance: u64,
+    /// Bitmask of the actions paused on this [Quarry], in addition to the
+    /// [Rewarder::paused_scopes].
+    pub paused_scopes: u8,
+    /// Timestamp when quarry rewards start, paired with [Quarry::famine_ts].
+    /// Tokens staked before then earn nothing until it.
+    pub start_ts: i64,
+    /// Rewards not yet distributed to stakers due to rounding, carried into the next update.
+    /// See [crate::payroll::Payroll::rewards_per_token_remainder].
+    pub rewards_per_token_remainder: u128,
+    /// Total fees charged on the rewards claimed by the quarry's miners, including referral fees.
+    pub total_claim_fees: u64,
+    /// [Quarry::total_rewards_accrued] as of the last time it was added to the [Rewarder].
+    pub rewards_accrued_reported: u64,
+    /// [Quarry::total_rewards_claimed] as of the last time it was added to the [Rewarder].
+    pub rewards_claimed_reported: u64,
+    /// [Quarry::total_claim_fees] as of the last time it was added to the [Rewarder].
+    pub claim_fees_reported: u64,
+    /// Recent values of the quarry's rewards per token, used to settle lock boosts
+    /// which expired before the [Miner] was next updated.
+    /// The snapshot at index `i` is taken every `REWARDS_SNAPSHOT_INTERVAL << i` seconds.
+    pub rewards_snapshots: [RewardsSnapshot; MAX_REWARDS_SNAPSHOTS],
+    /// Token account owned by the quarry which holds the early exit penalties redistributed
+    /// in the staked token, for quarries which do not stake the rewards token.
+    pub early_exit_penalty_vault: Pubkey,
+    /// Staked tokens paid out per staked token from redistributed early exit penalties.
+    /// Scaled like [Quarry::rewards_per_token_stored].
+    pub penalties_per_token_stored: u128,
+    /// Redistributed early exit penalties not yet paid out due to rounding.
+    pub penalties_per_token_remainder: u128,
+    /// Fraction of a token issued but not yet added to [Quarry::total_rewards_accrued],
+    /// in units of `1 / `[crate::payroll::REWARDS_DENOMINATOR].
+    pub rewards_issued_remainder: u128,
+    /// Unclaimed rewards given up by miners in emergency withdrawals.
+    /// These are included in [Quarry::total_rewards_accrued], but will never be claimed.
+    pub total_rewards_forfeited: u64,
+    /// The [Rewarder::rewards_epoch] the quarry was last synchronized with.
+    pub rewards_epoch: u64,
+    /// Number of [Miner]s closed with [quarry_mine::close_miner].
+    pub num_closed_miners: u64,
 }
 
 impl Quarry {
-    pub const LEN: usize = 32 + 32 + 1 + 2 + 1 + 8 + 8 + 16 + 8 + 8 + 8 + 8;
+    pub const LEN: usize = 32
+        + 32
+        + 1
+        + 2
+        + 1
+        + 8
+        + 8
+        + 16
+        + 8
+        + 8
+        + 8
+        + 8
+        + RewardStream::LEN * MAX_REWARD_STREAMS
+        + 8
+        + 8
+        + 8
+        + 8
+        + 1
+        + 8
+        + 8
+        + 8
+        + 8
+        + 32
+        + 8
+        + 8
+        + 32
+        + 8
+        + 8
+        + 1
+        + 8
+        + 16
+        + 8
+        + 8
+        + 8
+        + 8
+        + RewardsSnapshot::LEN * MAX_REWARDS_SNAPSHOTS
+        + 32
+        + 16
+        + 16
+        + 16
+        + 8
+        + 8
+        + 8;
+
+    /// Asserts that neither the [Rewarder] nor this [Quarry] pauses the `scope`.
+    pub fn assert_not_paused(&self, rewarder: &Rewarder, scope: u8) -> Result<()> {
+        rewarder.assert_not_paused()?;
+        invariant!(
+            (rewarder.paused_scopes | self.paused_scopes) & scope == 0,
+            ScopePaused
+        );
+        Ok(())
+    }
+
+    /// Returns true if the [Rewarder] or this [Quarry] pauses the `scope`.
+    pub fn is_paused(&self, rewarder: &Rewarder, scope: u8) -> bool {
+        rewarder.is_paused || (rewarder.paused_scopes | self.paused_scopes) & scope != 0
+    }
+
+    /// Total weight of the deposited tokens, including lock boosts.
+    /// Rewards are distributed pro rata to this amount.
+    pub fn total_weighted_tokens(&self) -> u64 {
+        // cannot overflow: checked whenever the boost changes
+        self.total_tokens_deposited
+            .saturating_add(self.total_boosted_tokens)
+    }
+}
+
+/// Maximum number of [RewardStream]s a [Quarry] may have.
+pub const MAX_REWARD_STREAMS: usize = 3;
+
+/// Number of [RewardsSnapshot]s kept by a [Quarry].
+pub const MAX_REWARDS_SNAPSHOTS: usize = 8;
+
+/// Seconds between the [RewardsSnapshot]s at index zero of [Quarry::rewards_snapshots].
+pub const REWARDS_SNAPSHOT_INTERVAL: i64 = 86_400;
+
+/// The rewards per token of a [Quarry] and its [RewardStream]s at a point in time.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub struct RewardsSnapshot {
+    /// When the snapshot was taken.
+    pub timestamp: i64,
+    /// [Quarry::rewards_per_token_stored] at the time.
+    pub rewards_per_token_stored: u128,
+    /// [RewardStream::rewards_per_token_stored] of each stream at the time.
+    pub stream_rewards_per_token_stored: [u128; MAX_REWARD_STREAMS],
+}
+
+impl RewardsSnapshot {
+    pub const LEN: usize = 8 + 16 + 16 * MAX_REWARD_STREAMS;
+}
+
+/// A secondary reward token streamed to the [Miner]s of a [Quarry],
+/// in addition to the [Rewarder]::rewards_token_mint.
+///
+/// The [Rewarder] must be a [quarry_mint_wrapper::Minter] of the stream's mint wrapper.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub struct RewardStream {
+    /// Mint wrapper which mints the stream's rewards.
+    pub mint_wrapper: Pubkey,
+    /// Mint of the stream's rewards token.
+    pub rewards_token_mint: Pubkey,
+    /// Amount of rewards distributed to the quarry per year.
+    pub annual_rewards_rate: u64,
+    /// Timestamp when stream rewards cease
+    pub famine_ts: i64,
+    /// Timestamp of last checkpoint
+    pub last_update_ts: i64,
+    /// Rewards per token stored in the stream
+    pub rewards_per_token_stored: u128,
+}
+
+impl RewardStream {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 8 + 16;
+
+    /// Returns true if this slot holds a stream.
+    pub fn is_active(&self) -> bool {
+        self.mint_wrapper != Pubkey::default()
+    }
+}
+
+/// A [Miner]'s checkpoint of a [RewardStream].
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub struct MinerRewardStream {
+    /// A checkpoint of the [RewardStream]'s reward tokens paid per staked token.
+    pub rewards_per_token_paid: u128,
+    /// Amount of stream tokens that the [Miner] may claim.
+    pub rewards_earned: u64,
+}
+
+impl MinerRewardStream {
+    pub const LEN: usize = 16 + 8;
 }
 
 /// An account that has staked tokens into a [Quarry].
@@ -125,10 +479,124 @@ pub struct Miner {
 
     /// Index of the [Miner].
     pub index: u64,
+
+    /// Checkpoints of the [Quarry]::reward_streams, by the same index.
+    pub reward_streams: [MinerRewardStream; 3],
+
+    /// Timestamp until which the [Miner::balance] may not be withdrawn.
+    pub lock_end_ts: i64,
+    /// Boost of the current lock, in BPS.
+    pub lock_boost_bps: u64,
+    /// Additional reward weight granted by the lock boost.
+    pub boosted_balance: u64,
+
+    /// Tokens which no longer earn rewards and are waiting to be withdrawn.
+    /// These remain in the [Miner::token_vault_key].
+    pub unbonding_balance: u64,
+    /// Timestamp after which the [Miner::unbonding_balance] may be withdrawn.
+    pub unbonding_end_ts: i64,
+
+    /// When the [Miner] last staked tokens.
+    pub last_stake_ts: i64,
+    /// Early exit penalty charged on the [Miner::unbonding_balance] when it is withdrawn.
+    pub unbonding_penalty: u64,
+    /// The [Quarry::allowlist_root] the [Miner]'s authority was last approved against.
+    pub allowlist_root: [u8; 32],
+    /// [MinerCheckpoints] recording the history of the [Miner::balance].
+    /// If default, the [Miner] does not record checkpoints.
+    pub checkpoints: Pubkey,
+    /// [Quarry::penalties_per_token_stored] as of the last time the [Miner] was updated.
+    pub penalties_per_token_paid: u128,
+    /// Redistributed early exit penalties which the [Miner] may claim, in the staked token.
+    pub penalties_earned: u64,
+    /// Tokens staked into the [Miner] by others which are still subject to the
+    /// early exit penalty of [Miner::last_staked_for_ts].
+    pub staked_for_balance: u64,
+    /// When tokens were last staked into the [Miner] by someone other than its authority.
+    pub last_staked_for_ts: i64,
 }
 
 impl Miner {
-    pub const LEN: usize = 32 + 32 + 1 + 32 + 8 + 16 + 8 + 8;
+    pub const LEN: usize = 32
+        + 32
+        + 1
+        + 32
+        + 8
+        + 16
+        + 8
+        + 8
+        + MinerRewardStream::LEN * MAX_REWARD_STREAMS
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 32
+        + 32
+        + 16
+        + 8
+        + 8
+        + 8;
+
+    /// Reward weight of the [Miner], including its lock boost.
+    pub fn weighted_balance(&self) -> u64 {
+        // cannot overflow: bounded by [Quarry::total_weighted_tokens]
+        self.balance.saturating_add(self.boosted_balance)
+    }
+
+    /// Returns true if the [Miner::balance] is locked.
+    pub fn is_locked(&self, current_ts: i64) -> bool {
+        current_ts < self.lock_end_ts
+    }
+}
+
+/// Number of [BalanceCheckpoint]s kept by [MinerCheckpoints].
+pub const MAX_BALANCE_CHECKPOINTS: usize = 64;
+
+/// The [Miner::balance] as of a timestamp.
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BalanceCheckpoint {
+    /// When the balance was recorded.
+    pub timestamp: i64,
+    /// [Miner::balance] from this timestamp onwards.
+    pub balance: u64,
+}
+
+impl BalanceCheckpoint {
+    pub const LEN: usize = 8 + 8;
+}
+
+/// History of the [Miner::balance] of a [Miner], for use as voting power.
+///
+/// Only the latest [MAX_BALANCE_CHECKPOINTS] changes are kept.
+#[account]
+#[derive(Copy, Debug)]
+pub struct MinerCheckpoints {
+    /// [Miner] whose balance is recorded.
+    pub miner: Pubkey,
+    /// Bump.
+    pub bump: u8,
+    /// Total number of [BalanceCheckpoint]s ever recorded.
+    pub num_checkpoints: u64,
+    /// Ring buffer of [BalanceCheckpoint]s, indexed by `num_checkpoints % MAX_BALANCE_CHECKPOINTS`.
+    pub checkpoints: [BalanceCheckpoint; MAX_BALANCE_CHECKPOINTS],
+}
+
+impl Default for MinerCheckpoints {
+    fn default() -> Self {
+        Self {
+            miner: Pubkey::default(),
+            bump: 0,
+            num_checkpoints: 0,
+            checkpoints: [BalanceCheckpoint::default(); MAX_BALANCE_CHECKPOINTS],
+        }
+    }
+}
+
+impl MinerCheckpoints {
+    pub const LEN: usize = 32 + 1 + 8 + BalanceCheckpoint::LEN * MAX_BALANCE_CHECKPOINTS;
 }
 
 #[cfg(test)]
@@ -148,8 +616,81 @@ mod tests {
         assert_eq!(Quarry::default().try_to_vec().unwrap().len(), Quarry::LEN);
     }
 
+    #[test]
+    fn test_paused_scopes() {
+        let mut rewarder = Rewarder::default();
+        let mut quarry = Quarry::default();
+        assert!(quarry.assert_not_paused(&rewarder, PAUSE_SCOPE_ALL).is_ok());
+
+        quarry.paused_scopes = PAUSE_SCOPE_STAKE;
+        assert!(quarry
+            .assert_not_paused(&rewarder, PAUSE_SCOPE_STAKE)
+            .is_err());
+        assert!(quarry
+            .assert_not_paused(&rewarder, PAUSE_SCOPE_WITHDRAW)
+            .is_ok());
+
+        rewarder.paused_scopes = PAUSE_SCOPE_CLAIM;
+        assert!(quarry
+            .assert_not_paused(&rewarder, PAUSE_SCOPE_CLAIM)
+            .is_err());
+        assert!(quarry
+            .assert_not_paused(&rewarder, PAUSE_SCOPE_WITHDRAW)
+            .is_ok());
+
+        rewarder.is_paused = true;
+        assert!(quarry
+            .assert_not_paused(&rewarder, PAUSE_SCOPE_WITHDRAW)
+            .is_err());
+    }
+
     #[test]
     fn test_miner_len() {
         assert_eq!(Miner::default().try_to_vec().unwrap().len(), Miner::LEN);
     }
+
+    #[test]
+    fn test_miner_checkpoints_len() {
+        assert_eq!(
+            MinerCheckpoints::default().try_to_vec().unwrap().len(),
+            MinerCheckpoints::LEN
+        );
+    }
+
+    #[test]
+    fn test_reward_stream_len() {
+        assert_eq!(
+            RewardStream::default().try_to_vec().unwrap().len(),
+            RewardStream::LEN
+        );
+        assert_eq!(
+            MinerRewardStream::default().try_to_vec().unwrap().len(),
+            MinerRewardStream::LEN
+        );
+        assert_eq!(Quarry::default().reward_streams.len(), MAX_REWARD_STREAMS);
+    }
+
+    #[test]
+    fn test_rewards_snapshot_len() {
+        assert_eq!(
+            RewardsSnapshot::default().try_to_vec().unwrap().len(),
+            RewardsSnapshot::LEN
+        );
+        assert_eq!(
+            Quarry::default().rewards_snapshots.len(),
+            MAX_REWARDS_SNAPSHOTS
+        );
+    }
+
+    #[test]
+    fn test_emission_schedule_len() {
+        assert_eq!(
+            EmissionSchedule::default().try_to_vec().unwrap().len(),
+            EmissionSchedule::LEN
+        );
+        assert_eq!(
+            EmissionSchedule::default().points.len(),
+            MAX_EMISSION_POINTS
+        );
+    }
 }
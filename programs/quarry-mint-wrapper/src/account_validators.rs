@@ -54,9 +54,9 @@ impl<'info> Validate<'info> for PerformMint<'info> {
     }
 }
 
-/// --------------------------------
-/// Account Structs
-/// --------------------------------
+// --------------------------------
+// Account Structs
+// --------------------------------
 
 impl<'info> Validate<'info> for OnlyAdmin<'info> {
     fn validate(&self) -> Result<()> {
@@ -1,6 +1,7 @@
 //! Proxy program for interacting with the token mint.
 #![deny(rustdoc::all)]
 #![allow(rustdoc::missing_doc_code_examples)]
+#![allow(clippy::result_large_err)]
 #![allow(deprecated)]
 
 #[macro_use]
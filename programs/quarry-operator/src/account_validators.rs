@@ -38,6 +38,18 @@ impl<'info> Validate<'info> for DelegateSetRewardsShare<'info> {
     }
 }
 
+impl<'info> Validate<'info> for DelegateCommitRewardsShares<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(
+            self.with_delegate.operator.share_allocator,
+            self.with_delegate.delegate,
+            Unauthorized
+        );
+        self.with_delegate.validate()?;
+        Ok(())
+    }
+}
+
 impl<'info> Validate<'info> for DelegateSetFamine<'info> {
     fn validate(&self) -> Result<()> {
         assert_keys_eq!(
@@ -55,6 +67,23 @@ impl<'info> Validate<'info> for DelegateSetFamine<'info> {
     }
 }
 
+impl<'info> Validate<'info> for DelegateSetDepositCaps<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(
+            self.quarry.rewarder,
+            self.with_delegate.rewarder,
+            Unauthorized
+        );
+        assert_keys_eq!(
+            self.with_delegate.operator.quarry_creator,
+            self.with_delegate.delegate,
+            Unauthorized
+        );
+        self.with_delegate.validate()?;
+        Ok(())
+    }
+}
+
 impl<'info> Validate<'info> for WithDelegate<'info> {
     fn validate(&self) -> Result<()> {
         assert_keys_eq!(self.operator.rewarder, self.rewarder);
@@ -1,6 +1,7 @@
 //! Delegates Quarry Rewarder authority roles.
 #![deny(rustdoc::all)]
 #![allow(rustdoc::missing_doc_code_examples)]
+#![allow(clippy::result_large_err)]
 #![allow(deprecated)]
 
 use anchor_lang::prelude::*;
@@ -57,7 +58,8 @@ pub mod quarry_operator {
         Ok(())
     }
 
-    /// Sets who can call [quarry_mine::quarry_mine::set_annual_rewards].
+    /// Sets who can call [quarry_mine::quarry_mine::set_annual_rewards]
+    /// and [quarry_mine::quarry_mine::set_emission_schedule].
     #[access_control(ctx.accounts.validate())]
     pub fn set_rate_setter(ctx: Context<SetRole>) -> Result<()> {
         let operator = &mut ctx.accounts.operator;
@@ -108,6 +110,30 @@ pub mod quarry_operator {
         Ok(())
     }
 
+    /// Calls [quarry_mine::quarry_mine::set_emission_schedule].
+    #[access_control(ctx.accounts.validate())]
+    pub fn delegate_set_emission_schedule(
+        ctx: Context<DelegateSetAnnualRewards>,
+        points: Vec<quarry_mine::EmissionPoint>,
+    ) -> Result<()> {
+        let operator = &ctx.accounts.with_delegate.operator;
+        let signer_seeds: &[&[&[u8]]] = &[gen_operator_signer_seeds!(operator)];
+        quarry_mine::cpi::set_emission_schedule(
+            CpiContext::new_with_signer(
+                ctx.accounts
+                    .with_delegate
+                    .quarry_mine_program
+                    .to_account_info(),
+                quarry_mine::cpi::accounts::SetEmissionSchedule {
+                    auth: ctx.accounts.with_delegate.to_auth_accounts(),
+                },
+                signer_seeds,
+            ),
+            points,
+        )?;
+        Ok(())
+    }
+
     /// Calls [quarry_mine::quarry_mine::create_quarry_v2].
     #[access_control(ctx.accounts.validate())]
     pub fn delegate_create_quarry(ctx: Context<DelegateCreateQuarry>, _bump: u8) -> Result<()> {
@@ -145,6 +171,49 @@ pub mod quarry_operator {
         Ok(())
     }
 
+    /// Calls [quarry_mine::quarry_mine::stage_rewards_share].
+    #[access_control(ctx.accounts.validate())]
+    pub fn delegate_stage_rewards_share(
+        ctx: Context<DelegateSetRewardsShare>,
+        new_share: u64,
+    ) -> Result<()> {
+        let operator = &ctx.accounts.with_delegate.operator;
+        let signer_seeds: &[&[&[u8]]] = &[gen_operator_signer_seeds!(operator)];
+        quarry_mine::cpi::stage_rewards_share(
+            CpiContext::new_with_signer(
+                ctx.accounts
+                    .with_delegate
+                    .quarry_mine_program
+                    .to_account_info(),
+                quarry_mine::cpi::accounts::SetRewardsShare {
+                    auth: ctx.accounts.with_delegate.to_auth_accounts(),
+                    quarry: ctx.accounts.quarry.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            new_share,
+        )?;
+        Ok(())
+    }
+
+    /// Calls [quarry_mine::quarry_mine::commit_rewards_shares].
+    #[access_control(ctx.accounts.validate())]
+    pub fn delegate_commit_rewards_shares(ctx: Context<DelegateCommitRewardsShares>) -> Result<()> {
+        let operator = &ctx.accounts.with_delegate.operator;
+        let signer_seeds: &[&[&[u8]]] = &[gen_operator_signer_seeds!(operator)];
+        quarry_mine::cpi::commit_rewards_shares(CpiContext::new_with_signer(
+            ctx.accounts
+                .with_delegate
+                .quarry_mine_program
+                .to_account_info(),
+            quarry_mine::cpi::accounts::CommitRewardsShares {
+                auth: ctx.accounts.with_delegate.to_auth_accounts(),
+            },
+            signer_seeds,
+        ))?;
+        Ok(())
+    }
+
     /// Calls [quarry_mine::quarry_mine::set_famine].
     #[access_control(ctx.accounts.validate())]
     pub fn delegate_set_famine(ctx: Context<DelegateSetFamine>, famine_ts: i64) -> Result<()> {
@@ -166,6 +235,33 @@ pub mod quarry_operator {
             famine_ts,
         )
     }
+
+    /// Calls [quarry_mine::quarry_mine::set_deposit_caps].
+    #[access_control(ctx.accounts.validate())]
+    pub fn delegate_set_deposit_caps(
+        ctx: Context<DelegateSetDepositCaps>,
+        max_total_tokens_deposited: u64,
+        max_miner_balance: u64,
+    ) -> Result<()> {
+        let operator = &ctx.accounts.with_delegate.operator;
+        let signer_seeds: &[&[&[u8]]] = &[gen_operator_signer_seeds!(operator)];
+
+        quarry_mine::cpi::set_deposit_caps(
+            CpiContext::new_with_signer(
+                ctx.accounts
+                    .with_delegate
+                    .quarry_mine_program
+                    .to_account_info(),
+                quarry_mine::cpi::accounts::SetDepositCaps {
+                    auth: ctx.accounts.with_delegate.to_readonly_auth_accounts(),
+                    quarry: ctx.accounts.quarry.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            max_total_tokens_deposited,
+            max_miner_balance,
+        )
+    }
 }
 
 // --------------------------------
@@ -185,14 +281,16 @@ pub struct SetRole<'info> {
     pub delegate: UncheckedAccount<'info>,
 }
 
-/// Accounts for [crate::quarry_operator::delegate_set_annual_rewards].
+/// Accounts for [crate::quarry_operator::delegate_set_annual_rewards]
+/// and [crate::quarry_operator::delegate_set_emission_schedule].
 #[derive(Accounts)]
 pub struct DelegateSetAnnualRewards<'info> {
     /// Delegate accounts.
     pub with_delegate: WithDelegate<'info>,
 }
 
-/// Accounts for [crate::quarry_operator::delegate_set_rewards_share].
+/// Accounts for [crate::quarry_operator::delegate_set_rewards_share]
+/// and [crate::quarry_operator::delegate_stage_rewards_share].
 #[derive(Accounts)]
 pub struct DelegateSetRewardsShare<'info> {
     /// Delegate accounts.
@@ -205,6 +303,13 @@ pub struct DelegateSetRewardsShare<'info> {
     pub quarry: Account<'info, Quarry>,
 }
 
+/// Accounts for [crate::quarry_operator::delegate_commit_rewards_shares].
+#[derive(Accounts)]
+pub struct DelegateCommitRewardsShares<'info> {
+    /// Delegate accounts.
+    pub with_delegate: WithDelegate<'info>,
+}
+
 /// Accounts for [crate::quarry_operator::delegate_set_famine].
 #[derive(Accounts)]
 pub struct DelegateSetFamine<'info> {
@@ -218,6 +323,19 @@ pub struct DelegateSetFamine<'info> {
     pub quarry: Account<'info, Quarry>,
 }
 
+/// Accounts for [crate::quarry_operator::delegate_set_deposit_caps].
+#[derive(Accounts)]
+pub struct DelegateSetDepositCaps<'info> {
+    /// Delegate accounts.
+    pub with_delegate: WithDelegate<'info>,
+    /// [Quarry].
+    #[account(
+        mut,
+        constraint = quarry.rewarder == with_delegate.rewarder.key()
+    )]
+    pub quarry: Account<'info, Quarry>,
+}
+
 /// Accounts struct for instructions that must be signed by one of the delegates on the [Operator].
 #[derive(Accounts, Clone)]
 pub struct WithDelegate<'info> {
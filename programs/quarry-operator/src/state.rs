@@ -16,11 +16,12 @@ pub struct Operator {
     /// Can modify the authorities below.
     pub admin: Pubkey,
 
-    /// Can call [quarry_mine::quarry_mine::set_annual_rewards].
+    /// Can call [quarry_mine::quarry_mine::set_annual_rewards] and [quarry_mine::quarry_mine::set_emission_schedule].
     pub rate_setter: Pubkey,
-    /// Can call [quarry_mine::quarry_mine::create_quarry].
+    /// Can call [quarry_mine::quarry_mine::create_quarry] and [quarry_mine::quarry_mine::set_deposit_caps].
     pub quarry_creator: Pubkey,
-    /// Can call [quarry_mine::quarry_mine::set_rewards_share].
+    /// Can call [quarry_mine::quarry_mine::set_rewards_share], [quarry_mine::quarry_mine::stage_rewards_share]
+    /// and [quarry_mine::quarry_mine::commit_rewards_shares].
     pub share_allocator: Pubkey,
 
     /// When the [Operator] was last modified.
@@ -1,6 +1,7 @@
 //! Registry to help the frontend quickly locate all active quarries.
 #![deny(rustdoc::all)]
 #![allow(rustdoc::missing_doc_code_examples)]
+#![allow(clippy::result_large_err)]
 
 use anchor_lang::prelude::*;
 use quarry_mine::{Quarry, Rewarder};